@@ -0,0 +1,877 @@
+/// What to render and how, beyond the log itself. Build it up from [`Options::default`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Options {
+    /// Join up the shapes of consecutive days with the same highlight.
+    pub connect_streaks: bool,
+    /// Describe the longest streak of each year underneath its calendar.
+    pub longest_streak: bool,
+    /// The style to use where the log's `[style]` table doesn't say otherwise.
+    pub theme: Style,
+    /// Only draw the days on or after this date.
+    pub from: Option<Date>,
+    /// Only draw the days on or before this date.
+    pub to: Option<Date>,
+    /// Outline this day and fade the ones after it.
+    pub as_of: Option<Date>,
+    /// Follow every year's page with a page for each of its months, and link between them.
+    pub month_pages: bool,
+}
+
+impl Options {
+    /// Set [`Options::connect_streaks`].
+    pub fn connect_streaks(mut self, connect_streaks: bool) -> Self {
+        self.connect_streaks = connect_streaks;
+        self
+    }
+    /// Set [`Options::longest_streak`].
+    pub fn longest_streak(mut self, longest_streak: bool) -> Self {
+        self.longest_streak = longest_streak;
+        self
+    }
+    /// Set [`Options::theme`].
+    pub fn theme(mut self, theme: Style) -> Self {
+        self.theme = theme;
+        self
+    }
+    /// Set [`Options::from`].
+    pub fn from(mut self, from: Date) -> Self {
+        self.from = Some(from);
+        self
+    }
+    /// Set [`Options::to`].
+    pub fn to(mut self, to: Date) -> Self {
+        self.to = Some(to);
+        self
+    }
+    /// Set [`Options::as_of`].
+    pub fn as_of(mut self, as_of: Date) -> Self {
+        self.as_of = Some(as_of);
+        self
+    }
+    /// Set [`Options::month_pages`].
+    pub fn month_pages(mut self, month_pages: bool) -> Self {
+        self.month_pages = month_pages;
+        self
+    }
+
+    /// The style to draw `log` in.
+    pub fn style(&self, log: &Log) -> Style {
+        self.theme.with(log.style())
+    }
+}
+
+/// A fully laid out calendar, independent of any output format.
+#[derive(Debug)]
+pub(crate) struct Scene {
+    pub pages: Vec<Page>,
+}
+
+impl Scene {
+    /// Draw every page of the scene, in order.
+    pub(crate) fn render(&self, renderer: &mut impl Renderer) {
+        for page in &self.pages {
+            renderer.add_page(&page.label, page.size);
+            for item in &page.items {
+                match item {
+                    Item::Text(text) => renderer.draw_text(text),
+                    &Item::Rect { rect, colour } => renderer.draw_rect(rect, colour),
+                    &Item::Circle {
+                        centre,
+                        radius,
+                        colour,
+                    } => renderer.draw_circle(centre, radius, colour),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Page {
+    /// A short name for the page that can go in file names.
+    pub label: String,
+    /// What the page is called in bookmarks and the like.
+    pub title: String,
+    pub size: (Mm, Mm),
+    /// Places on the page worth jumping to, in reading order.
+    pub anchors: Vec<Anchor>,
+    pub links: Vec<Link>,
+    /// The index of the page that this one shows part of in more detail, if any.
+    pub detail_of: Option<usize>,
+    /// Drawn in order, so later items go on top of earlier ones.
+    pub items: Vec<Item>,
+}
+
+impl Page {
+    fn new(label: String, title: String, size: (Mm, Mm)) -> Self {
+        Self {
+            label,
+            title,
+            size,
+            anchors: Vec::new(),
+            links: Vec::new(),
+            detail_of: None,
+            items: Vec::new(),
+        }
+    }
+    fn rect(&mut self, rect: (Mm, Mm, Mm, Mm), colour: Colour) {
+        self.items.push(Item::Rect { rect, colour });
+    }
+    /// Draw a border of `width` just inside `rect`.
+    fn outline(&mut self, (left, top, width, height): (Mm, Mm, Mm, Mm), line: Mm, colour: Colour) {
+        self.rect((left, top, width, line), colour);
+        self.rect((left, top + height - line, width, line), colour);
+        self.rect((left, top, line, height), colour);
+        self.rect((left + width - line, top, line, height), colour);
+    }
+    fn circle(&mut self, centre: (Mm, Mm), radius: Mm, colour: Colour) {
+        self.items.push(Item::Circle {
+            centre,
+            radius,
+            colour,
+        });
+    }
+}
+
+/// A named place on a page, such as the top left corner of a month.
+#[derive(Debug)]
+pub(crate) struct Anchor {
+    pub name: String,
+    pub position: (Mm, Mm),
+}
+
+/// An area of a page that leads to another page when clicked.
+#[derive(Debug)]
+pub(crate) struct Link {
+    /// The left, top, width and height.
+    pub rect: (Mm, Mm, Mm, Mm),
+    /// The index of the page in the scene.
+    pub target: usize,
+}
+
+/// Something drawn on a page. All positions are measured from the top left corner of the page.
+#[derive(Debug)]
+pub(crate) enum Item {
+    Text(TextRun),
+    Rect {
+        /// The left, top, width and height.
+        rect: (Mm, Mm, Mm, Mm),
+        colour: Colour,
+    },
+    Circle {
+        centre: (Mm, Mm),
+        radius: Mm,
+        colour: Colour,
+    },
+}
+
+/// A line of text in a single font.
+#[derive(Debug)]
+pub(crate) struct TextRun {
+    pub style: FontStyle,
+    /// The font size in points.
+    pub size: f32,
+    pub colour: Colour,
+    /// Where the text's baseline starts.
+    pub position: (Mm, Mm),
+    pub content: String,
+}
+
+/// Turns a scene into some output format.
+pub(crate) trait Renderer {
+    /// Start a new page, which everything drawn afterward goes onto.
+    fn add_page(&mut self, label: &str, size: (Mm, Mm));
+    fn draw_text(&mut self, text: &TextRun);
+    fn draw_rect(&mut self, rect: (Mm, Mm, Mm, Mm), colour: Colour);
+    fn draw_circle(&mut self, centre: (Mm, Mm), radius: Mm, colour: Colour);
+}
+
+/// Lay out one page for every year of the log, each followed by a page for every month if
+/// `options.month_pages` is set.
+pub(crate) fn calendar(log: &Log, options: &Options, fonts: &Fonts) -> Scene {
+    let style = options.style(log);
+    let grid = Grid {
+        log,
+        options,
+        style: &style,
+        fonts,
+    };
+
+    let mut scene = Scene { pages: Vec::new() };
+    for page_year in PageYear::all(log, style.first_month.0) {
+        let page_index = scene.pages.len();
+        let page_x = Mm(style.page_width);
+        let page_y = Mm(style.page_height);
+
+        let mut page = Page::new(page_year.label(), page_year.title(), (page_x, page_y));
+        page.rect((Mm(0.0), Mm(0.0), page_x, page_y), style.background);
+
+        let title_text = text!(&fonts.bold, "{}", page_year.title())
+            .size(style.title_size)
+            .colour(style.text_colour)
+            .center();
+        let title_vpad = Mm(style.title_padding);
+        let y = title_vpad + title_text.height();
+        let title_text = title_text.position((page_x / 2.0, y));
+        title_text.draw(&mut page);
+
+        let x_margin = Mm(style.margin);
+        let x_sep = Mm(style.column_gap);
+        let top_margin = y + title_vpad;
+        let bottom_margin = title_vpad;
+        let col_width = month_width(&style);
+        let row_height = (page_y - top_margin - bottom_margin) / 4.0;
+        for (month_index, (year, month)) in (0..).zip(page_year.months()) {
+            let row = month_index / 3;
+            let col = month_index % 3;
+            let left = x_margin + (col_width + x_sep) * f64::from(col);
+            let top = top_margin + row_height * f64::from(row);
+            let (header, _) = grid.month(&mut page, (year, month), (left, top), 1.0);
+            page.anchors.push(Anchor {
+                name: format!("{month} {year}"),
+                position: (left, top),
+            });
+            if options.month_pages {
+                page.links.push(Link {
+                    rect: header,
+                    target: page_index + 1 + month_index as usize,
+                });
+            }
+        }
+
+        if options.longest_streak {
+            let (first, last) = (page_year.first_day(), page_year.last_day());
+            let streaks = streaks::find(log, first..=last);
+            // `max_by_key` picks the last maximum, so reverse to get the earliest.
+            if let Some(streak) = streaks.into_iter().rev().max_by_key(Streak::days) {
+                let days = streak.days();
+                let text = text!(
+                    &fonts.italic,
+                    "Longest streak: {}, {days} day{} ({} {} – {} {})",
+                    streak.highlight.name(),
+                    if days == 1 { "" } else { "s" },
+                    streak.start.day(),
+                    streak.start.month(),
+                    streak.end.day(),
+                    streak.end.month(),
+                )
+                .size(style.caption_size)
+                .colour(style.text_colour)
+                .center();
+                let y = page_y - (bottom_margin - text.height()) / 2.0;
+                text.position((page_x / 2.0, y)).draw(&mut page);
+            }
+        }
+
+        scene.pages.push(page);
+        if options.month_pages {
+            for month in page_year.months() {
+                scene.pages.push(grid.month_page(month, page_index));
+            }
+        }
+    }
+    scene
+}
+
+/// The width of each month on a year's page, where they are laid out three abreast.
+fn month_width(style: &Style) -> Mm {
+    (Mm(style.page_width) - Mm(style.margin) * 2.0 - Mm(style.column_gap) * 2.0) / 3.0
+}
+
+/// Draws months of the log.
+struct Grid<'a> {
+    log: &'a Log,
+    options: &'a Options,
+    style: &'a Style,
+    fonts: &'a Fonts,
+}
+
+impl<'a> Grid<'a> {
+    /// Draw a month's header and grid with its top left corner at `(left, top)`, at `scale` times
+    /// the size it has on a year's page. Returns the rectangle of the header and the bottom of the
+    /// grid.
+    fn month(
+        &self,
+        page: &mut Page,
+        (year, month): (i32, Month),
+        (left, top): (Mm, Mm),
+        scale: f32,
+    ) -> ((Mm, Mm, Mm, Mm), Mm) {
+        let (log, options, style, fonts) = (self.log, self.options, self.style, self.fonts);
+        let length = |mm: f64| Mm(mm) * f64::from(scale);
+        let col_width = month_width(style) * f64::from(scale);
+
+        let header_padding = length(style.header_padding);
+        let header_text = text!(&fonts.bold, "{month}")
+            .size(style.header_size * scale)
+            .colour(style.header_text_colour)
+            .center();
+        let center_line = left + col_width / 2.0;
+        let header_y = top + header_padding + header_text.height();
+        let header_text = header_text.position((center_line, header_y));
+
+        let bg_height = header_text.height() + header_padding * 2.0;
+        let header = (left, top, col_width, bg_height);
+        page.rect(header, style.header_colour);
+
+        header_text.draw(page);
+
+        let first = Date::from_calendar_date(year, month, 1).unwrap();
+        let first_col = style.week_start.position(first.weekday());
+
+        // Week numbers take a column half as wide as a day's, to the left of the days.
+        let show_week_numbers = style.week_numbers != WeekNumbers::None;
+        let inner_col_width = col_width / if show_week_numbers { 7.5 } else { 7.0 };
+        let week_number_width = col_width - inner_col_width * 7.0;
+        let week_number_x = left + week_number_width / 2.0;
+        let left = left + week_number_width;
+        let vspacing = length(style.day_spacing);
+
+        // Shade non-working days with a band running from the weekday initial down through
+        // every week of the month, underneath any highlights. Rectangles are drawn inset on these
+        // days so that the band still shows.
+        let days_in_month = time::util::days_in_year_month(year, month);
+        let weeks = (first_col + days_in_month - 1) / 7 + 1;
+        let row_height = text!(&fonts.regular, "0")
+            .size(style.day_size * scale)
+            .height()
+            + vspacing * 2.0;
+        for (col, weekday) in (0..).zip(style.week_start.week()) {
+            if style.non_working_days.contains(weekday) {
+                let left = left + inner_col_width * f64::from(col);
+                let height = row_height * f64::from(weeks + 1);
+                let rect = (left, top + bg_height, inner_col_width, height);
+                page.rect(rect, style.non_working_colour);
+            }
+        }
+        for (col, weekday) in style.week_start.week().into_iter().enumerate() {
+            let text = text!(&fonts.italic, "{}", &weekday.to_string()[..1])
+                .size(style.weekday_size * scale)
+                .colour(style.weekday_colour)
+                .center();
+            let x = left + inner_col_width * col as f64 + inner_col_width / 2.0;
+            let y = top + bg_height + text.height() + vspacing;
+            text.position((x, y)).draw(page);
+        }
+        for row in 0..weeks {
+            let row_start = first - Duration::days(i64::from(first_col) - 7 * i64::from(row));
+            let Some(number) = style.week_numbers.number(row_start) else {
+                break;
+            };
+            let text = text!(&fonts.italic, "{number}")
+                .size(style.week_number_size * scale)
+                .colour(style.week_number_colour)
+                .center();
+            // Centred on the row, since it may be smaller than the day numbers.
+            let y = top + bg_height + row_height * (f64::from(row) + 1.5) + text.height() / 2.0;
+            text.position((week_number_x, y)).draw(page);
+        }
+        let last = Date::from_calendar_date(year, month, days_in_month).unwrap();
+        let mut logged = log.range(first..=last).peekable();
+        for day in 1..=days_in_month {
+            let date = Date::from_calendar_date(year, month, day).unwrap();
+            let holiday = log.holiday(date).is_some();
+            let future = options.as_of.is_some_and(|as_of| date > as_of);
+            let text = text!(&fonts.regular, "{day}")
+                .size(style.day_size * scale)
+                .colour(if future {
+                    style.future_text_colour
+                } else if holiday {
+                    style.holiday_text_colour
+                } else {
+                    style.text_colour
+                })
+                .center();
+            let row = (first_col + day - 1) / 7;
+            let col = style.week_start.position(date.weekday());
+            let left = left + inner_col_width * f64::from(col);
+            let top = top + bg_height + (text.height() + vspacing * 2.0) * f64::from(row + 1);
+            let x = left + inner_col_width / 2.0;
+            let y = top + vspacing + text.height();
+
+            if holiday {
+                let height = text.height() + vspacing * 2.0;
+                page.rect((left, top, inner_col_width, height), style.holiday_colour);
+            }
+
+            let highlight = logged
+                .next_if(|&(logged, _)| logged == date)
+                .and_then(|(_, h)| h);
+            let next = logged.peek().and_then(|&(_, highlight)| highlight);
+            let streak_continues = options.connect_streaks
+                && col != 6
+                && day != days_in_month
+                && next.zip(highlight).is_some_and(|(a, b)| ptr::eq(a, b));
+            match highlight.map(|highlight| (highlight.colour(), highlight.shape())) {
+                Some((colour, Shape::Circle)) => {
+                    let y = y - text.height() / 2.0;
+                    let radius = text.height() + length(style.highlight_padding);
+                    if streak_continues {
+                        let rect = (x, y - radius, inner_col_width, radius * 2.0);
+                        page.rect(rect, colour);
+                    }
+                    page.circle((x, y), radius, colour);
+                }
+                Some((colour, Shape::Rectangle)) => {
+                    // a tiny bit of overlap avoids tiny white bars
+                    let width = inner_col_width + Mm(0.1);
+                    let height = text.height() + vspacing * 2.0 + Mm(0.1);
+                    // On non-working days, the band stays visible above and below, while runs of
+                    // rectangles still join up from side to side.
+                    let inset = if style.non_working_days.contains(date.weekday()) {
+                        height * 0.15
+                    } else {
+                        Mm(0.0)
+                    };
+                    page.rect((left, top + inset, width, height - inset * 2.0), colour);
+                }
+                None => {}
+            }
+            if options.as_of == Some(date) {
+                let rect = (left, top, inner_col_width, text.height() + vspacing * 2.0);
+                page.outline(rect, length(style.today_line_width), style.today_colour);
+            }
+
+            text.position((x, y)).draw(page);
+        }
+
+        let bottom = top + bg_height + row_height * f64::from(weeks + 1);
+        (header, bottom)
+    }
+
+    /// A page with one month across its whole width, followed by the holidays, labels and notes of
+    /// its days. The title links back to the year's page, `year_page`.
+    fn month_page(&self, (year, month): (i32, Month), year_page: usize) -> Page {
+        let (style, fonts) = (self.style, self.fonts);
+        let page_x = Mm(style.page_width);
+        let page_y = Mm(style.page_height);
+        let title = format!("{month} {year}");
+        let label = format!("{year}-{:02}", u8::from(month));
+        let mut page = Page::new(label, title.clone(), (page_x, page_y));
+        page.detail_of = Some(year_page);
+        page.rect((Mm(0.0), Mm(0.0), page_x, page_y), style.background);
+
+        let title_text = text!(&fonts.bold, "{title}")
+            .size(style.title_size)
+            .colour(style.text_colour)
+            .center();
+        let title_vpad = Mm(style.title_padding);
+        let (width, height) = (title_text.width(), title_text.height());
+        let y = title_vpad + height;
+        title_text.position((page_x / 2.0, y)).draw(&mut page);
+        page.links.push(Link {
+            rect: ((page_x - width) / 2.0, title_vpad, width, height),
+            target: year_page,
+        });
+
+        let margin = Mm(style.margin);
+        let scale = ((page_x - margin * 2.0) / month_width(style)) as f32;
+        let (_, bottom) = self.month(&mut page, (year, month), (margin, y + title_vpad), scale);
+
+        let mut y = bottom + title_vpad;
+        for day in 1..=time::util::days_in_year_month(year, month) {
+            let date = Date::from_calendar_date(year, month, day).unwrap();
+            let annotation = self.log.annotation(date);
+            let parts = [
+                self.log.holiday(date),
+                annotation.and_then(|annotation| annotation.label.as_deref()),
+                annotation.and_then(|annotation| annotation.note.as_deref()),
+            ];
+            let parts = parts.into_iter().flatten().collect::<Vec<_>>();
+            if parts.is_empty() {
+                continue;
+            }
+            let text = text!(&fonts.regular, "{day}: {}", parts.join(" – "))
+                .size(style.caption_size)
+                .colour(style.text_colour);
+            y += text.height() * 1.8;
+            if y > page_y - margin {
+                break;
+            }
+            text.position((margin, y)).draw(&mut page);
+        }
+        page
+    }
+}
+
+/// The twelve months shown together on a page, which start from `first_month` and so don't have to
+/// line up with calendar years.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PageYear {
+    first_day: Date,
+}
+
+impl PageYear {
+    pub(crate) fn containing(date: Date, first_month: Month) -> Self {
+        let year = if u8::from(date.month()) >= u8::from(first_month) {
+            date.year()
+        } else {
+            date.year() - 1
+        };
+        Self {
+            first_day: Date::from_calendar_date(year, first_month, 1).unwrap(),
+        }
+    }
+
+    /// Every page year that contains a day of the log, in order.
+    pub(crate) fn all(log: &Log, first_month: Month) -> Vec<Self> {
+        let last = Self::containing(log.end_date(), first_month);
+        let mut page_years = vec![Self::containing(log.start_date(), first_month)];
+        while *page_years.last().unwrap() != last {
+            page_years.push(page_years.last().unwrap().next());
+        }
+        page_years
+    }
+
+    pub(crate) fn first_day(self) -> Date {
+        self.first_day
+    }
+
+    pub(crate) fn last_day(self) -> Date {
+        self.next().first_day.previous_day().unwrap()
+    }
+
+    fn next(self) -> Self {
+        let year = self.first_day.year() + 1;
+        Self {
+            first_day: Date::from_calendar_date(year, self.first_day.month(), 1).unwrap(),
+        }
+    }
+
+    /// The year and month of each month in order.
+    pub(crate) fn months(self) -> impl Iterator<Item = (i32, Month)> {
+        let mut month = (self.first_day.year(), self.first_day.month());
+        (0..12).map(move |_| {
+            let current = month;
+            month = match month.1 {
+                Month::December => (month.0 + 1, Month::January),
+                _ => (month.0, month.1.next()),
+            };
+            current
+        })
+    }
+
+    /// Like "2023", or "2023–24" if the page runs over two calendar years.
+    pub(crate) fn title(self) -> String {
+        self.label().replace('-', "–")
+    }
+
+    /// Like `title`, but with only ASCII characters so that it can go in file names.
+    pub(crate) fn label(self) -> String {
+        let year = self.first_day.year();
+        match self.first_day.month() {
+            Month::January => year.to_string(),
+            _ => format!("{year}-{:02}", (year + 1).rem_euclid(100)),
+        }
+    }
+}
+
+/// The space left between pages when they are stacked into a single file.
+pub(crate) const PAGE_GAP: Mm = Mm(10.0);
+
+pub(crate) struct Fonts {
+    pub regular: Font,
+    pub bold: Font,
+    pub italic: Font,
+}
+
+impl Fonts {
+    pub(crate) fn load(style: &Style) -> Result<Self, Error> {
+        Ok(Self {
+            regular: Font::new(&style.regular_font, FontStyle::Regular)?,
+            bold: Font::new(&style.bold_font, FontStyle::Bold)?,
+            italic: Font::new(&style.italic_font, FontStyle::Italic)?,
+        })
+    }
+
+    pub(crate) fn get(&self, style: FontStyle) -> &Font {
+        match style {
+            FontStyle::Regular => &self.regular,
+            FontStyle::Bold => &self.bold,
+            FontStyle::Italic => &self.italic,
+        }
+    }
+}
+
+pub(crate) struct Font {
+    pub style: FontStyle,
+    /// The contents of the font file.
+    pub data: Vec<u8>,
+    pub face: rusttype::Font<'static>,
+    /// The name other programs know the font by, if it has one.
+    pub family: Option<String>,
+}
+
+impl Font {
+    fn new(path: &str, style: FontStyle) -> Result<Self, Error> {
+        let data = fs::read(path).map_err(|source| Error::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        let face = rusttype::Font::try_from_vec(data.clone()).ok_or_else(|| Error::Font {
+            path: path.to_owned(),
+        })?;
+        let family = subset::family_name(&data);
+        Ok(Self {
+            style,
+            data,
+            face,
+            family,
+        })
+    }
+
+    /// The scale of text in this font at a size of `size` points.
+    pub(crate) fn scale(&self, size: f32) -> rusttype::Scale {
+        let metrics = self.face.v_metrics_unscaled();
+        let units_per_em = f32::from(self.face.units_per_em());
+        let glyph_height = (metrics.ascent - metrics.descent) / units_per_em;
+        rusttype::Scale::uniform(glyph_height * size)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FontStyle {
+    Regular,
+    Bold,
+    Italic,
+}
+
+pub(crate) struct Text<'font> {
+    pub font: &'font Font,
+    pub size: f32,
+    pub colour: Colour,
+    pub position: (Mm, Mm),
+    pub align: Align,
+    pub content: String,
+}
+
+macro_rules! text {
+    ($font:expr, $($rest:tt)*) => {
+        Text {
+            font: $font,
+            size: 12.0,
+            colour: Colour([0, 0, 0]),
+            position: (Mm(0.0), Mm(0.0)),
+            align: Align::Left,
+            content: format!($($rest)*),
+        }
+    }
+}
+use text;
+
+impl Text<'_> {
+    fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+    fn colour(mut self, colour: Colour) -> Self {
+        self.colour = colour;
+        self
+    }
+    fn center(mut self) -> Self {
+        self.align = Align::Center;
+        self
+    }
+    fn position(mut self, position: (Mm, Mm)) -> Self {
+        self.position = position;
+        self
+    }
+    fn scale(&self) -> rusttype::Scale {
+        self.font.scale(self.size)
+    }
+    fn height(&self) -> Mm {
+        let metrics = self.font.face.v_metrics(self.scale());
+        to_mm(metrics.ascent + metrics.descent)
+    }
+    fn width(&self) -> Mm {
+        let scale = self.scale();
+
+        let mut width = 0.0;
+        let mut last_glyph = None;
+        for glyph in self.font.face.glyphs_for(self.content.chars()) {
+            let glyph = glyph.scaled(scale);
+            if let &Some(last_glyph) = &last_glyph {
+                width += self.font.face.pair_kerning(scale, last_glyph, glyph.id());
+            }
+            width += glyph.h_metrics().advance_width;
+            last_glyph = Some(glyph.id());
+        }
+
+        to_mm(width)
+    }
+    fn draw(self, page: &mut Page) {
+        let shift_left = match self.align {
+            Align::Left => Mm(0.0),
+            Align::Center => self.width() / 2.0,
+        };
+        page.items.push(Item::Text(TextRun {
+            style: self.font.style,
+            size: self.size,
+            colour: self.colour,
+            position: (self.position.0 - shift_left, self.position.1),
+            content: self.content,
+        }));
+    }
+}
+
+pub(crate) enum Align {
+    Left,
+    Center,
+}
+
+pub(crate) fn to_mm(pt: f32) -> Mm {
+    Mm::from(Pt(f64::from(pt)))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn calendar_layout() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            b = { shape = \"rectangle\", colour = \"#0000FF\" }\n\
+            [data]\n\
+            2022-12-31.Sat = \"b\"\n\
+            2023-01-01.Sun = \"a\"\n\
+            2023-01-02.Mon = \"a\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let fonts = Fonts::load(&Style::default()).unwrap();
+        let scene = calendar(&log, &Options::default(), &fonts);
+
+        let labels = scene.pages.iter().map(|page| &*page.label);
+        assert_eq!(labels.collect::<Vec<_>>(), ["2022", "2023"]);
+        let page = &scene.pages[1];
+        assert_eq!((page.size.0 .0, page.size.1 .0), (210.0, 297.0));
+
+        let texts = (page.items.iter())
+            .filter_map(|item| match item {
+                Item::Text(text) => Some(&*text.content),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts[..3], ["2023", "January", "M"]);
+        // a title, then for each month a header, seven weekdays and every day
+        assert_eq!(texts.len(), 1 + 12 * 8 + 365);
+
+        let circles = (page.items.iter())
+            .filter_map(|item| match item {
+                &Item::Circle { centre, .. } => Some(centre),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(circles.len(), 2);
+        // 1 January 2023 is a Sunday, so it ends the first row and 2 January starts the next.
+        assert!(circles[0].0 > circles[1].0);
+        assert!(circles[0].1 < circles[1].1);
+
+        let rects = scene.pages[0].items.iter();
+        let rects = rects
+            .filter(|item| matches!(item, Item::Rect { .. }))
+            .count();
+        // the background, the month headers, a weekend band for each month and the highlight
+        assert_eq!(rects, 1 + 12 + 12 * 2 + 1);
+
+        let as_of = Date::from_calendar_date(2023, Month::January, 1).unwrap();
+        let options = Options {
+            as_of: Some(as_of),
+            ..Options::default()
+        };
+        let scene = calendar(&log, &options, &fonts);
+        let rects = (scene.pages[1].items.iter())
+            .filter(|item| matches!(item, Item::Rect { .. }))
+            .count();
+        // the same, but the rectangle highlight is on the other page and there's a four-sided outline
+        assert_eq!(rects, 1 + 12 + 12 * 2 + 4);
+        let style = Style::default();
+        let colours = (scene.pages[1].items.iter())
+            .filter_map(|item| match item {
+                Item::Text(text) if text.content == "1" || text.content == "2" => Some(text.colour),
+                _ => None,
+            })
+            .map(|colour| colour.0)
+            .take(2)
+            .collect::<Vec<_>>();
+        assert_eq!(colours, [style.text_colour.0, style.future_text_colour.0]);
+    }
+
+    #[test]
+    fn month_pages() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2023-03-04.Sat = { highlight = \"a\", label = \"Party\" }\n"
+            .parse::<Log>()
+            .unwrap();
+        let fonts = Fonts::load(&Style::default()).unwrap();
+        let options = Options {
+            month_pages: true,
+            ..Options::default()
+        };
+        let scene = calendar(&log, &options, &fonts);
+        assert_eq!(scene.pages.len(), 13);
+
+        let year = &scene.pages[0];
+        assert_eq!(year.detail_of, None);
+        let targets = year.links.iter().map(|link| link.target);
+        assert_eq!(targets.collect::<Vec<_>>(), (1..=12).collect::<Vec<_>>());
+
+        let march = &scene.pages[3];
+        assert_eq!(march.label, "2023-03");
+        assert_eq!(march.title, "March 2023");
+        assert_eq!(march.detail_of, Some(0));
+        assert_eq!(march.links.len(), 1);
+        assert_eq!(march.links[0].target, 0);
+        let mut texts = (march.items.iter()).filter_map(|item| match item {
+            Item::Text(text) => Some(&*text.content),
+            _ => None,
+        });
+        assert_eq!(texts.next_back(), Some("4: Party"));
+    }
+
+    #[test]
+    fn academic_years() {
+        let date = Date::from_calendar_date(2024, Month::March, 5).unwrap();
+        let page_year = PageYear::containing(date, Month::September);
+        assert_eq!(page_year.title(), "2023–24");
+        assert_eq!(page_year.label(), "2023-24");
+        assert_eq!(page_year.first_day().to_string(), "2023-09-01");
+        assert_eq!(page_year.last_day().to_string(), "2024-08-31");
+        let months = page_year.months().collect::<Vec<_>>();
+        assert_eq!(months[0], (2023, Month::September));
+        assert_eq!(months[4], (2024, Month::January));
+        assert_eq!(months[11], (2024, Month::August));
+
+        let page_year = PageYear::containing(date, Month::January);
+        assert_eq!(page_year.title(), "2024");
+        assert_eq!(page_year.last_day().to_string(), "2024-12-31");
+    }
+
+    use super::calendar;
+    use super::Fonts;
+    use super::Item;
+    use super::Options;
+    use super::PageYear;
+    use crate::log::Log;
+    use crate::style::Style;
+    use time::Date;
+    use time::Month;
+}
+
+use crate::log::Colour;
+use crate::log::Log;
+use crate::log::Shape;
+use crate::streaks;
+use crate::streaks::Streak;
+use crate::style::Style;
+use crate::style::WeekNumbers;
+use crate::subset;
+use crate::Error;
+use printpdf::Mm;
+use printpdf::Pt;
+use std::fs;
+use std::ptr;
+use time::Date;
+use time::Duration;
+use time::Month;
@@ -0,0 +1,100 @@
+/// Anything that can go wrong reading, summarising or rendering a log.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading the log or writing the output failed.
+    Io(io::Error),
+    /// The log is invalid.
+    Parse(ParseError),
+    /// A file the style refers to, such as a font, couldn't be read.
+    Read {
+        /// The file.
+        path: String,
+        /// Why it couldn't be read.
+        source: io::Error,
+    },
+    /// An output file couldn't be saved.
+    Save {
+        /// The file.
+        path: String,
+        /// Why it couldn't be saved.
+        source: io::Error,
+    },
+    /// A theme is neither built in nor a readable file.
+    UnknownTheme {
+        /// The name or path given.
+        theme: String,
+        /// Why it couldn't be read as a file.
+        source: io::Error,
+    },
+    /// A theme file is invalid.
+    Theme {
+        /// The path of the theme file.
+        theme: String,
+        /// What is wrong with it.
+        source: ParseError,
+    },
+    /// A font file isn't a font that can be drawn with.
+    Font {
+        /// The font file.
+        path: String,
+    },
+    /// The PDF couldn't be put together.
+    Pdf(Box<dyn StdError + Send + Sync>),
+    /// There are no logged days in the range that was asked for.
+    NoDays,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::Read { path, .. } => write!(f, "failed to read {path}"),
+            Self::Save { path, .. } => write!(f, "failed to save {path}"),
+            Self::UnknownTheme { theme, .. } => {
+                let names = THEMES.map(|(name, _)| name).join(", ");
+                write!(
+                    f,
+                    "`{theme}` is neither a built-in theme ({names}) nor a readable file"
+                )
+            }
+            Self::Theme { theme, .. } => write!(f, "failed to parse theme `{theme}`"),
+            Self::Font { path } => write!(f, "failed to load font {path}"),
+            Self::Pdf(_) => f.write_str("error generating PDF"),
+            Self::NoDays => f.write_str("no logged days in the given range"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Read { source, .. } | Self::Save { source, .. } => Some(source),
+            Self::UnknownTheme { source, .. } => Some(source),
+            Self::Theme { source, .. } => Some(source),
+            Self::Pdf(e) => Some(&**e),
+            Self::Io(_) | Self::Parse(_) | Self::Font { .. } | Self::NoDays => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+use crate::log::ParseError;
+use crate::style::THEMES;
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
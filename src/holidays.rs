@@ -0,0 +1,355 @@
+/// A country or region with its own public holidays, written as an ISO 3166 code like `"GB-ENG"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Region {
+    /// England, which shares its holidays with Wales.
+    GbEng,
+    GbWls,
+    GbSct,
+    GbNir,
+    /// Federal holidays of the United States.
+    Us,
+    /// Holidays common to every German state.
+    De,
+    DeBw,
+    DeBy,
+    DeBe,
+    DeNw,
+}
+
+const REGIONS: [(&str, Region); 10] = [
+    ("GB-ENG", Region::GbEng),
+    ("GB-WLS", Region::GbWls),
+    ("GB-SCT", Region::GbSct),
+    ("GB-NIR", Region::GbNir),
+    ("US", Region::Us),
+    ("DE", Region::De),
+    ("DE-BW", Region::DeBw),
+    ("DE-BY", Region::DeBy),
+    ("DE-BE", Region::DeBe),
+    ("DE-NW", Region::DeNw),
+];
+
+impl Serialize for Region {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (code, _) = REGIONS
+            .iter()
+            .find(|&&(_, region)| region == *self)
+            .unwrap();
+        serializer.serialize_str(code)
+    }
+}
+
+impl<'de> Deserialize<'de> for Region {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const CODES: [&str; REGIONS.len()] = {
+            let mut codes = [""; REGIONS.len()];
+            let mut i = 0;
+            while i < REGIONS.len() {
+                codes[i] = REGIONS[i].0;
+                i += 1;
+            }
+            codes
+        };
+        let code = String::deserialize(deserializer)?;
+        (REGIONS.iter())
+            .find(|(c, _)| *c == code)
+            .map(|&(_, region)| region)
+            .ok_or_else(|| de::Error::unknown_variant(&code, &CODES))
+    }
+}
+
+/// The public holidays of any of `regions` from `from` to `to` inclusive. When regions name the
+/// same day differently, the names are joined with slashes.
+pub(crate) fn between(regions: &[Region], from: Date, to: Date) -> BTreeMap<Date, String> {
+    let mut holidays = BTreeMap::<Date, String>::new();
+    for &region in regions {
+        // Substitute and observed days can fall in the neighbouring years.
+        for year in from.year() - 1..=to.year() + 1 {
+            for (date, name) in in_year(region, year) {
+                if date < from || date > to {
+                    continue;
+                }
+                let names = holidays.entry(date).or_default();
+                if !names.split(" / ").any(|n| n == name) {
+                    if !names.is_empty() {
+                        names.push_str(" / ");
+                    }
+                    names.push_str(&name);
+                }
+            }
+        }
+    }
+    holidays
+}
+
+/// The holidays of `region` that stem from `year`, in no particular order.
+fn in_year(region: Region, year: i32) -> Vec<(Date, String)> {
+    let easter = easter(year);
+    let mut days = Vec::<(Date, &str)>::new();
+    match region {
+        Region::GbEng | Region::GbWls | Region::GbSct | Region::GbNir => {
+            days.push((date(year, January, 1), "New Year's Day"));
+            if region == Region::GbSct {
+                days.push((date(year, January, 2), "2nd January"));
+            }
+            if region == Region::GbNir {
+                days.push((date(year, March, 17), "St Patrick's Day"));
+            }
+            days.push((easter - Duration::days(2), "Good Friday"));
+            if region != Region::GbSct {
+                days.push((easter + Duration::days(1), "Easter Monday"));
+            }
+            let early_may = match year {
+                1995 | 2020 => date(year, May, 8),
+                _ => nth_weekday(year, May, Monday, 1),
+            };
+            days.push((early_may, "Early May bank holiday"));
+            let spring = match year {
+                2002 | 2012 => date(year, June, 4),
+                2022 => date(year, June, 2),
+                _ => last_weekday(year, May, Monday),
+            };
+            days.push((spring, "Spring bank holiday"));
+            if region == Region::GbNir {
+                days.push((date(year, July, 12), "Battle of the Boyne"));
+            }
+            let summer = match region {
+                Region::GbSct => nth_weekday(year, August, Monday, 1),
+                _ => last_weekday(year, August, Monday),
+            };
+            days.push((summer, "Summer bank holiday"));
+            if region == Region::GbSct {
+                days.push((date(year, November, 30), "St Andrew's Day"));
+            }
+            days.push((date(year, December, 25), "Christmas Day"));
+            days.push((date(year, December, 26), "Boxing Day"));
+
+            let one_off = match year {
+                1999 => &[(December, 31, "Millennium Eve")][..],
+                2002 => &[(June, 3, "Golden Jubilee")],
+                2011 => &[(April, 29, "Royal wedding")],
+                2012 => &[(June, 5, "Diamond Jubilee")],
+                2022 => &[
+                    (June, 3, "Platinum Jubilee"),
+                    (September, 19, "State funeral of Queen Elizabeth II"),
+                ],
+                2023 => &[(May, 8, "Coronation of King Charles III")],
+                _ => &[],
+            };
+            days.extend(one_off.iter().map(|&(m, d, name)| (date(year, m, d), name)));
+
+            with_substitutes(days)
+        }
+        Region::Us => {
+            let mut fixed = vec![
+                (date(year, January, 1), "New Year's Day"),
+                (date(year, July, 4), "Independence Day"),
+                (date(year, November, 11), "Veterans Day"),
+                (date(year, December, 25), "Christmas Day"),
+            ];
+            if year >= 2021 {
+                fixed.push((date(year, June, 19), "Juneteenth"));
+            }
+            if year >= 1986 {
+                days.push((
+                    nth_weekday(year, January, Monday, 3),
+                    "Martin Luther King Jr. Day",
+                ));
+            }
+            days.push((
+                nth_weekday(year, February, Monday, 3),
+                "Washington's Birthday",
+            ));
+            days.push((last_weekday(year, May, Monday), "Memorial Day"));
+            days.push((nth_weekday(year, September, Monday, 1), "Labor Day"));
+            days.push((nth_weekday(year, October, Monday, 2), "Columbus Day"));
+            days.push((nth_weekday(year, November, Thursday, 4), "Thanksgiving Day"));
+
+            let mut holidays = (days.into_iter())
+                .map(|(date, name)| (date, name.to_owned()))
+                .collect::<Vec<_>>();
+            // Holidays on a Saturday are observed the day before, and on a Sunday the day after.
+            for (date, name) in fixed {
+                let observed = match date.weekday() {
+                    Saturday => Some(date.previous_day().unwrap()),
+                    Sunday => Some(date.next_day().unwrap()),
+                    _ => None,
+                };
+                if let Some(observed) = observed {
+                    holidays.push((observed, format!("{name} (observed)")));
+                }
+                holidays.push((date, name.to_owned()));
+            }
+            holidays
+        }
+        Region::De | Region::DeBw | Region::DeBy | Region::DeBe | Region::DeNw => {
+            days.push((date(year, January, 1), "New Year's Day"));
+            if matches!(region, Region::DeBw | Region::DeBy) {
+                days.push((date(year, January, 6), "Epiphany"));
+            }
+            if region == Region::DeBe && year >= 2019 {
+                days.push((date(year, March, 8), "International Women's Day"));
+            }
+            days.push((easter - Duration::days(2), "Good Friday"));
+            days.push((easter + Duration::days(1), "Easter Monday"));
+            days.push((date(year, May, 1), "Labour Day"));
+            days.push((easter + Duration::days(39), "Ascension Day"));
+            days.push((easter + Duration::days(50), "Whit Monday"));
+            if matches!(region, Region::DeBw | Region::DeBy | Region::DeNw) {
+                days.push((easter + Duration::days(60), "Corpus Christi"));
+            }
+            if region == Region::DeBy {
+                days.push((date(year, August, 15), "Assumption Day"));
+            }
+            days.push((date(year, October, 3), "German Unity Day"));
+            if year == 2017 {
+                days.push((date(year, October, 31), "Reformation Day"));
+            }
+            if matches!(region, Region::DeBw | Region::DeBy | Region::DeNw) {
+                days.push((date(year, November, 1), "All Saints' Day"));
+            }
+            days.push((date(year, December, 25), "Christmas Day"));
+            days.push((date(year, December, 26), "Second Day of Christmas"));
+
+            (days.into_iter())
+                .map(|(date, name)| (date, name.to_owned()))
+                .collect()
+        }
+    }
+}
+
+/// Add a substitute day for every holiday that falls on a weekend: the next weekday that isn't
+/// already a holiday, as in the UK.
+fn with_substitutes(mut days: Vec<(Date, &str)>) -> Vec<(Date, String)> {
+    days.sort_by_key(|&(date, _)| date);
+    let is_weekend = |date: Date| matches!(date.weekday(), Saturday | Sunday);
+    let mut taken = (days.iter())
+        .map(|&(date, _)| date)
+        .filter(|&date| !is_weekend(date))
+        .collect::<Vec<_>>();
+    let mut holidays = Vec::new();
+    for (date, name) in days {
+        if is_weekend(date) {
+            let mut substitute = date.next_day().unwrap();
+            while is_weekend(substitute) || taken.contains(&substitute) {
+                substitute = substitute.next_day().unwrap();
+            }
+            taken.push(substitute);
+            holidays.push((substitute, format!("{name} (substitute day)")));
+        }
+        holidays.push((date, name.to_owned()));
+    }
+    holidays
+}
+
+/// Easter Sunday in the Gregorian calendar, by the anonymous algorithm.
+fn easter(year: i32) -> Date {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    date(year, Month::try_from(month as u8).unwrap(), day as u8)
+}
+
+fn date(year: i32, month: Month, day: u8) -> Date {
+    Date::from_calendar_date(year, month, day).unwrap()
+}
+
+/// The `n`th `weekday` of the month, counting from one.
+fn nth_weekday(year: i32, month: Month, weekday: Weekday, n: u8) -> Date {
+    let first = date(year, month, 1);
+    let offset =
+        (7 + weekday.number_days_from_monday() - first.weekday().number_days_from_monday()) % 7;
+    date(year, month, 1 + offset + 7 * (n - 1))
+}
+
+fn last_weekday(year: i32, month: Month, weekday: Weekday) -> Date {
+    let last = date(year, month, time::util::days_in_year_month(year, month));
+    let offset =
+        (7 + last.weekday().number_days_from_monday() - weekday.number_days_from_monday()) % 7;
+    last - Duration::days(i64::from(offset))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn easter_sundays() {
+        assert_eq!(easter(2019).to_string(), "2019-04-21");
+        assert_eq!(easter(2024).to_string(), "2024-03-31");
+        assert_eq!(easter(2038).to_string(), "2038-04-25");
+    }
+
+    #[test]
+    fn england_and_scotland() {
+        let holidays = holidays_in(&[Region::GbEng], 2022);
+        assert_eq!(holidays["2022-01-03"], "New Year's Day (substitute day)");
+        assert_eq!(holidays["2022-04-18"], "Easter Monday");
+        assert_eq!(holidays["2022-06-02"], "Spring bank holiday");
+        assert_eq!(holidays["2022-08-29"], "Summer bank holiday");
+        assert_eq!(holidays["2022-12-27"], "Christmas Day (substitute day)");
+        assert_eq!(holidays["2022-12-26"], "Boxing Day");
+        assert_eq!(holidays.len(), 12);
+
+        let holidays = holidays_in(&[Region::GbSct], 2021);
+        assert_eq!(holidays["2021-01-04"], "2nd January (substitute day)");
+        assert_eq!(holidays["2021-08-02"], "Summer bank holiday");
+        assert_eq!(holidays["2021-11-30"], "St Andrew's Day");
+        assert_eq!(holidays["2021-12-28"], "Boxing Day (substitute day)");
+    }
+
+    #[test]
+    fn united_states_and_bavaria() {
+        let holidays = holidays_in(&[Region::Us], 2021);
+        assert_eq!(holidays["2021-01-18"], "Martin Luther King Jr. Day");
+        assert_eq!(holidays["2021-06-18"], "Juneteenth (observed)");
+        assert_eq!(holidays["2021-11-25"], "Thanksgiving Day");
+        assert_eq!(holidays["2021-12-31"], "New Year's Day (observed)");
+
+        let holidays = holidays_in(&[Region::DeBy, Region::GbEng], 2024);
+        assert_eq!(holidays["2024-05-09"], "Ascension Day");
+        assert_eq!(holidays["2024-05-30"], "Corpus Christi");
+        assert_eq!(holidays["2024-03-29"], "Good Friday");
+        assert_eq!(
+            holidays["2024-12-26"],
+            "Second Day of Christmas / Boxing Day"
+        );
+    }
+
+    fn holidays_in(regions: &[Region], year: i32) -> BTreeMap<String, String> {
+        let from = Date::from_ordinal_date(year, 1).unwrap();
+        let to = Date::from_calendar_date(year, Month::December, 31).unwrap();
+        (between(regions, from, to).into_iter())
+            .map(|(date, name)| (date.to_string(), name))
+            .collect()
+    }
+
+    use super::between;
+    use super::easter;
+    use super::Region;
+    use std::collections::BTreeMap;
+    use time::Date;
+    use time::Month;
+}
+
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::collections::BTreeMap;
+use time::Date;
+use time::Duration;
+use time::Month;
+use time::Month::*;
+use time::Weekday;
+use time::Weekday::*;
@@ -0,0 +1,261 @@
+//! HTML output.
+
+/// Render the days of the log chosen by `options` to a single HTML page at `path`. See
+/// [`write()`].
+pub fn render(log: &Log, options: &Options, path: &str) -> Result<(), Error> {
+    crate::write_file(path, |out| write(log, options, out))
+}
+
+/// Write the days of the log chosen by `options` as a single HTML page that needs nothing else to
+/// display: styles and scripts are all inline. With `as_of`, that day is outlined and the days
+/// after it are faded.
+pub fn write(log: &Log, options: &Options, mut out: impl io::Write) -> Result<(), Error> {
+    let log = &crate::chosen_days(log, options)?;
+    let style = &options.style(log);
+    let as_of = options.as_of;
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n\
+        <html lang=\"en\">\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n",
+    );
+    writeln!(
+        html,
+        "<title>{}</title>\n<style>",
+        Escape(log.document().title())
+    )
+    .unwrap();
+    html.push_str(STYLE);
+    // The fonts are linked rather than embedded, so browsers fall back on their own if the files
+    // aren't where the style says.
+    for (path, face) in [
+        (&style.regular_font, ""),
+        (&style.bold_font, " font-weight: bold;"),
+        (&style.italic_font, " font-style: italic;"),
+    ] {
+        writeln!(
+            html,
+            "@font-face {{ font-family: calendar;{face} src: url(\"{}\"); }}",
+            CssEscape(path)
+        )
+        .unwrap();
+    }
+    writeln!(
+        html,
+        "body {{ font-family: calendar, sans-serif; background: {}; color: {}; }}",
+        hex(style.background),
+        hex(style.text_colour)
+    )
+    .unwrap();
+    writeln!(
+        html,
+        "caption {{ background: {}; color: {}; }}\nth {{ color: {}; }}",
+        hex(style.header_colour),
+        hex(style.header_text_colour),
+        hex(style.weekday_colour)
+    )
+    .unwrap();
+    writeln!(
+        html,
+        ".off {{ background: {}; }}",
+        hex(style.non_working_colour)
+    )
+    .unwrap();
+    writeln!(
+        html,
+        ".holiday {{ background: {}; color: {}; }}",
+        hex(style.holiday_colour),
+        hex(style.holiday_text_colour)
+    )
+    .unwrap();
+    writeln!(
+        html,
+        ".today > span {{ outline: {}mm solid {}; }}\n.future {{ color: {}; }}",
+        style.today_line_width,
+        hex(style.today_colour),
+        hex(style.future_text_colour)
+    )
+    .unwrap();
+    for (i, highlight) in log.highlights().iter().enumerate() {
+        writeln!(html, ".h{i} {{ --colour: {}; }}", hex(highlight.colour())).unwrap();
+        writeln!(html, "body.hide-h{i} td.h{i} {{ --colour: transparent; }}").unwrap();
+    }
+    html.push_str("</style>\n</head>\n<body>\n");
+
+    html.push_str("<div class=\"legend\">\n");
+    for (i, highlight) in log.highlights().iter().enumerate() {
+        writeln!(
+            html,
+            "<button type=\"button\" data-highlight=\"{i}\" aria-pressed=\"true\">\
+            <span class=\"swatch h{i} {}\"><span></span></span>{}</button>",
+            shape_class(highlight.shape()),
+            Escape(highlight.name()),
+        )
+        .unwrap();
+    }
+    if log.has_holidays() {
+        html.push_str(
+            "<span class=\"legend-entry\"><span class=\"swatch holiday\"></span>\
+            Public holiday</span>\n",
+        );
+    }
+    html.push_str("</div>\n");
+
+    for page_year in PageYear::all(log, style.first_month.0) {
+        let title = page_year.title();
+        writeln!(html, "<section>\n<h1>{title}</h1>\n<div class=\"months\">").unwrap();
+        for (year, month) in page_year.months() {
+            writeln!(html, "<table>\n<caption>{month}</caption>").unwrap();
+            html.push_str("<thead><tr>");
+            for weekday in style.week_start.week() {
+                let class = non_working_class(style, weekday);
+                let initial = &weekday.to_string()[..1];
+                write!(html, "<th{class}>{initial}</th>").unwrap();
+            }
+            html.push_str("</tr></thead>\n<tbody>\n<tr>");
+
+            let first = Date::from_calendar_date(year, month, 1).unwrap();
+            for _ in 0..style.week_start.position(first.weekday()) {
+                html.push_str("<td></td>");
+            }
+            let days = time::util::days_in_year_month(year, month);
+            let last = Date::from_calendar_date(year, month, days).unwrap();
+            let mut highlights = log.range(first..=last).peekable();
+            for day in 1..=days {
+                let date = Date::from_calendar_date(year, month, day).unwrap();
+                if day != 1 && date.weekday() == style.week_start.0 {
+                    html.push_str("</tr>\n<tr>");
+                }
+                let logged = highlights.next_if(|&(logged, _)| logged == date);
+                let holiday = log.holiday(date);
+
+                let mut title = format!("{date} ({})", date.weekday());
+                let mut classes = Vec::new();
+                if style.non_working_days.contains(date.weekday()) {
+                    classes.push("off".to_owned());
+                }
+                match as_of {
+                    Some(as_of) if date == as_of => classes.push("today".to_owned()),
+                    Some(as_of) if date > as_of => classes.push("future".to_owned()),
+                    _ => {}
+                }
+                if let Some(name) = holiday {
+                    classes.push("holiday".to_owned());
+                    write!(title, "\n{name}").unwrap();
+                }
+                if let Some((_, Some(highlight))) = logged {
+                    let i = (log.highlights().iter())
+                        .position(|h| ptr::eq(h, highlight))
+                        .unwrap();
+                    classes.push(format!("h{i}"));
+                    classes.push(shape_class(highlight.shape()).to_owned());
+                    write!(title, "\n{}", highlight.name()).unwrap();
+                }
+                if let Some(annotation) = log.annotation(date) {
+                    for text in [&annotation.label, &annotation.note].into_iter().flatten() {
+                        write!(title, "\n{text}").unwrap();
+                    }
+                }
+
+                html.push_str("<td");
+                if !classes.is_empty() {
+                    write!(html, " class=\"{}\"", classes.join(" ")).unwrap();
+                }
+                // Days outside the log only get a tooltip if there's something to say about them.
+                if logged.is_some() || holiday.is_some() {
+                    write!(html, " title=\"{}\"", Escape(&title)).unwrap();
+                }
+                write!(html, "><span>{day}</span></td>").unwrap();
+            }
+            html.push_str("</tr>\n</tbody>\n</table>\n");
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    writeln!(html, "<script>\n{SCRIPT}</script>\n</body>\n</html>").unwrap();
+    out.write_all(html.as_bytes())?;
+    Ok(())
+}
+
+/// A class attribute marking non-working days, or nothing for working days.
+fn non_working_class(style: &Style, weekday: Weekday) -> &'static str {
+    if style.non_working_days.contains(weekday) {
+        " class=\"off\""
+    } else {
+        ""
+    }
+}
+
+fn shape_class(shape: Shape) -> &'static str {
+    match shape {
+        Shape::Circle => "circle",
+        Shape::Rectangle => "rectangle",
+    }
+}
+
+/// Escapes text for a double-quoted CSS string inside a `<style>` element.
+struct CssEscape<'a>(&'a str);
+
+impl Display for CssEscape<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '"' | '\\' | '<' | '\n' => write!(f, "\\{:X} ", u32::from(c))?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+const STYLE: &str = "\
+body { margin: 2em; }
+h1 { text-align: center; }
+.legend { display: flex; flex-wrap: wrap; gap: 0.5em; justify-content: center; }
+.legend button { display: flex; align-items: center; gap: 0.4em; font: inherit; \
+padding: 0.3em 0.6em; border: 1px solid #999; border-radius: 4px; background: none; \
+color: inherit; cursor: pointer; }
+.legend-entry { display: flex; align-items: center; gap: 0.4em; padding: 0.3em 0.6em; }
+.legend button[aria-pressed=\"false\"] { opacity: 0.4; text-decoration: line-through; }
+.swatch { display: inline-block; width: 1.2em; height: 1.2em; }
+.swatch > span { display: block; width: 100%; height: 100%; }
+.months { display: grid; grid-template-columns: repeat(3, 1fr); gap: 1.5em; \
+max-width: 60em; margin: 0 auto; }
+table { border-collapse: collapse; width: 100%; table-layout: fixed; }
+caption { font-weight: bold; padding: 0.2em; }
+th { font-style: italic; font-weight: normal; padding: 0.3em 0; }
+td { text-align: center; padding: 0; height: 2em; }
+td > span { display: inline-block; width: 1.8em; line-height: 1.8em; }
+td.rectangle { box-shadow: inset 0 0 0 10em var(--colour); }
+.swatch.rectangle > span { background: var(--colour); }
+.circle > span { background: var(--colour); border-radius: 50%; }
+td[title] { cursor: default; }
+";
+
+const SCRIPT: &str = "\
+for (const button of document.querySelectorAll('.legend button')) {
+    button.addEventListener('click', () => {
+        const shown = button.getAttribute('aria-pressed') === 'true';
+        button.setAttribute('aria-pressed', String(!shown));
+        document.body.classList.toggle('hide-h' + button.dataset.highlight, shown);
+    });
+}
+";
+
+use crate::draw::Options;
+use crate::draw::PageYear;
+use crate::log::Log;
+use crate::log::Shape;
+use crate::style::Style;
+use crate::svg::hex;
+use crate::svg::Escape;
+use crate::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Write as _;
+use std::io;
+use std::ptr;
+use time::Date;
+use time::Weekday;
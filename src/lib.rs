@@ -0,0 +1,252 @@
+//! Parse logs of highlighted days and render them as calendars.
+//!
+//! A [`Log`] is read from the TOML format described in the README, and can then be queried by date
+//! or highlight, or drawn in any [`Output`] format:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), calendar::Error> {
+//! let log = calendar::Log::from_reader(std::fs::File::open("log.toml")?)?;
+//! if let Some(holiday) = log.highlight("holiday") {
+//!     for date in log.dates_with(holiday) {
+//!         println!("{date}");
+//!     }
+//! }
+//!
+//! let output = calendar::Output::Pdf(calendar::pdf::Options::default());
+//! output.render(&log, &calendar::Options::default(), "calendar")?;
+//! # Ok(())
+//! # }
+//! ```
+#![feature(portable_simd)]
+#![warn(missing_docs)]
+
+/// A format to render a log to.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Output {
+    /// A single document with a page for every year.
+    Pdf(pdf::Options),
+    /// Vector images, with a file for every page by default.
+    Svg(svg::Options),
+    /// Raster images, with a file for every page by default.
+    Png(raster::Options),
+    /// A single page with styles and scripts inline.
+    Html,
+    /// Month grids as plain text.
+    Text,
+    /// A table for every month.
+    Markdown,
+}
+
+impl Output {
+    /// The name of the format, for messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pdf(_) => "PDF",
+            Self::Svg(_) => "SVG",
+            Self::Png(_) => "PNG",
+            Self::Html => "HTML",
+            Self::Text => "text",
+            Self::Markdown => "Markdown",
+        }
+    }
+
+    /// The extension of files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Pdf(_) => "pdf",
+            Self::Svg(_) => "svg",
+            Self::Png(_) => "png",
+            Self::Html => "html",
+            Self::Text => "txt",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// Render the days of `log` chosen by `options` to files named `stem` plus an extension. SVG
+    /// and PNG output make a file for every page, unless they are asked for a single file.
+    pub fn render(&self, log: &Log, options: &Options, stem: &str) -> Result<(), Error> {
+        match self {
+            Self::Svg(svg_options) if !svg_options.single_file => {
+                svg::render(log, options, svg_options, stem)
+            }
+            Self::Png(png_options) if !png_options.single_file => {
+                raster::render(log, options, png_options, stem)
+            }
+            _ => write_file(&format!("{stem}.{}", self.extension()), |out| {
+                self.write(log, options, out)
+            }),
+        }
+    }
+
+    /// Render the days of `log` chosen by `options` to `out`. SVG and PNG output always stack every
+    /// page into one image.
+    pub fn write(&self, log: &Log, options: &Options, mut out: impl Write) -> Result<(), Error> {
+        match self {
+            Self::Pdf(pdf_options) => pdf::write(log, options, pdf_options, out),
+            Self::Svg(svg_options) => svg::write(log, options, svg_options, out),
+            Self::Png(png_options) => raster::write(log, options, png_options, out),
+            Self::Html => html::write(log, options, out),
+            Self::Text => {
+                let log = &chosen_days(log, options)?;
+                let style = options.style(log);
+                Ok(terminal::write_plain(log, &style, options.as_of, &mut out)?)
+            }
+            Self::Markdown => {
+                let log = &chosen_days(log, options)?;
+                let style = options.style(log);
+                Ok(markdown::write(log, &style, options.as_of, &mut out)?)
+            }
+        }
+    }
+
+    /// Render the days of `log` chosen by `options` in memory, like [`Output::write`].
+    pub fn to_bytes(&self, log: &Log, options: &Options) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        self.write(log, options, &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// The log cut down to the days that `options` asks for.
+pub(crate) fn chosen_days(log: &Log, options: &Options) -> Result<Log, Error> {
+    // The whole log has already been parsed and checked, but only the chosen days are drawn.
+    log.between(options.from, options.to).ok_or(Error::NoDays)
+}
+
+/// Build a file in memory and then save it, via a temporary file so that the old file is left alone
+/// if saving fails.
+pub(crate) fn write_file(
+    path: &str,
+    write: impl FnOnce(&mut Vec<u8>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut contents = Vec::new();
+    write(&mut contents)?;
+    let temp = format!("{path}.tmp");
+    (|| {
+        fs::write(&temp, contents)?;
+        fs::rename(&temp, path)
+    })()
+    .map_err(|source| Error::Save {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn in_memory() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2023-01-01.Sun = \"a\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let options = Options::default();
+        let bytes = |output: Output| output.to_bytes(&log, &options).unwrap();
+
+        let pdf = bytes(Output::Pdf(pdf::Options::default()));
+        assert!(pdf.starts_with(b"%PDF-"));
+        // Without a colour profile, and with every stream compressed, a PDF stays small.
+        let pdf = lopdf::Document::load_mem(&pdf).unwrap();
+        assert!(pdf.catalog().unwrap().get(b"OutputIntents").is_err());
+        for object in pdf.objects.values() {
+            if let lopdf::Object::Stream(stream) = object {
+                assert!(stream.dict.has(b"Filter"), "{:?}", stream.dict);
+            }
+        }
+        assert!(bytes(Output::Png(raster::Options::default())).starts_with(b"\x89PNG\r\n"));
+        let svg = bytes(Output::Svg(svg::Options::default()));
+        assert!(svg.starts_with(b"<svg ") && svg.ends_with(b"</svg>\n"));
+        assert!(bytes(Output::Html).starts_with(b"<!DOCTYPE html>"));
+        assert!(bytes(Output::Markdown).starts_with(b"# Calendar\n"));
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("font-family=\"'DejaVu Sans', sans-serif\""));
+
+        // HTML takes its colours from the style, like the drawn formats.
+        let dark = Options::default().theme(Style::theme("dark").unwrap());
+        let html = String::from_utf8(Output::Html.to_bytes(&log, &dark).unwrap()).unwrap();
+        assert!(html.contains("body { font-family: calendar, sans-serif; background: #1E1E1E;"));
+        assert!(html.contains("caption { background: #3C3C3C; color: #FFFFFF; }"));
+
+        let mut out = Vec::new();
+        Output::Text.write(&log, &options, &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("January"));
+
+        let later = Date::from_calendar_date(2023, Month::February, 1).unwrap();
+        let options = Options::default().from(later);
+        let result = Output::Html.to_bytes(&log, &options);
+        assert!(matches!(result, Err(Error::NoDays)));
+        // The format modules choose days the same way when used directly.
+        let result = svg::write(&log, &options, &svg::Options::default(), Vec::new());
+        assert!(matches!(result, Err(Error::NoDays)));
+        let result = html::write(&log, &options, Vec::new());
+        assert!(matches!(result, Err(Error::NoDays)));
+    }
+
+    use super::html;
+    use super::pdf;
+    use super::raster;
+    use super::svg;
+    use super::Error;
+    use super::Log;
+    use super::Options;
+    use super::Output;
+    use super::Style;
+    use printpdf::lopdf;
+    use time::Date;
+    use time::Month;
+}
+
+pub use error::Error;
+mod error;
+
+pub use log::Annotation;
+pub use log::Colour;
+pub use log::Days;
+pub use log::Document;
+pub use log::Highlight;
+pub use log::Log;
+pub use log::ParseError;
+pub use log::Run;
+pub use log::Shape;
+pub use log::Snippet;
+mod log;
+
+mod runs;
+
+mod holidays;
+
+mod subset;
+
+pub use stats::Stats;
+mod stats;
+
+mod streaks;
+
+pub use draw::Options;
+mod draw;
+
+pub use style::DayOfWeek;
+pub use style::MonthNumber;
+pub use style::Overrides as StyleOverrides;
+pub use style::Style;
+pub use style::WeekNumbers;
+pub use style::Weekdays;
+mod style;
+
+pub mod pdf;
+
+pub mod svg;
+
+pub mod raster;
+
+pub mod html;
+
+pub mod terminal;
+
+pub mod markdown;
+
+use std::fs;
+use std::io::Write;
@@ -1,8 +1,43 @@
-#[derive(Debug)]
-pub(crate) struct Log {
+/// A log of highlighted days, parsed from the `log.toml` format with [`str::parse`] or
+/// [`Log::from_reader`].
+#[derive(Debug, PartialEq)]
+pub struct Log {
+    regions: Vec<holidays::Region>,
+    document: Document,
+    style: style::Overrides,
     highlights: Vec<Highlight>,
     start_date: Date,
-    days: Vec<Day>,
+    days: Runs,
+    annotations: BTreeMap<Date, Annotation>,
+    /// The names of public holidays, from the start of the year before the log to the end of the
+    /// year after it.
+    holidays: BTreeMap<Date, String>,
+}
+
+/// Details about the calendar as a whole, from the `[document]` table.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Document {
+    /// The title of the whole calendar. See [`Document::title`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Who made the calendar.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// What the calendar is about.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Words to find the calendar by.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+impl Document {
+    /// The title, or a generic one if the log doesn't give one.
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or("Calendar")
+    }
 }
 
 impl FromStr for Log {
@@ -10,42 +45,349 @@ impl FromStr for Log {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         toml::Deserializer::new(s)
             .deserialize_map(DeVisitor)
-            .map_err(ParseError)
+            .map_err(|e| ParseError::new(&e, s))
     }
 }
 
+/// A log that failed to parse, with the span of the source it failed at.
 #[derive(Debug)]
-pub(crate) struct ParseError(toml::de::Error);
+pub struct ParseError {
+    message: String,
+    span: Range<usize>,
+}
+
+impl ParseError {
+    /// `error`, from parsing `source`.
+    pub(crate) fn new(error: &toml::de::Error, source: &str) -> Self {
+        Self {
+            message: error.message().to_owned(),
+            // Errors about the document as a whole (such as a missing section) come with either no
+            // span or an empty one at the very start; point at the end instead, since that is
+            // where the missing content would go.
+            span: match error.span() {
+                Some(span) if span != (0..0) => span,
+                _ => source.len()..source.len(),
+            },
+        }
+    }
+
+    /// Display the error alongside the line of `source` it occurred on, with the offending part
+    /// underlined.
+    pub fn snippet<'a>(&'a self, source: &'a str, path: &'a str) -> Snippet<'a> {
+        Snippet {
+            error: self,
+            source,
+            path,
+        }
+    }
+}
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.write_str("failed to parse log")
+        f.write_str(&self.message)
     }
 }
 
-impl Error for ParseError {
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        Some(&self.0)
+impl Error for ParseError {}
+
+/// A [`ParseError`] shown in the context of its source. See [`ParseError::snippet`].
+pub struct Snippet<'a> {
+    error: &'a ParseError,
+    source: &'a str,
+    path: &'a str,
+}
+
+impl Display for Snippet<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Range { start, end } = self.error.span;
+        let line_start = self.source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.source[start..]
+            .find('\n')
+            .map_or(self.source.len(), |i| start + i);
+        let line = &self.source[line_start..line_end];
+        let line_number = self.source[..start].matches('\n').count() + 1;
+        let column = self.source[line_start..start].chars().count() + 1;
+        let underlined = self.source[start..end.min(line_end)].chars().count().max(1);
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        writeln!(f, "error: {}", self.error)?;
+        writeln!(f, "{gutter}--> {}:{line_number}:{column}", self.path)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line_number} | {}", line.trim_end_matches('\r'))?;
+        writeln!(
+            f,
+            "{gutter} | {}{}",
+            " ".repeat(column - 1),
+            "^".repeat(underlined)
+        )
     }
 }
 
 impl Log {
+    /// Read and parse a whole log. Parse errors are returned as [`crate::Error::Parse`], which can be shown
+    /// in context with [`ParseError::snippet`].
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, crate::Error> {
+        let mut source = String::new();
+        reader.read_to_string(&mut source)?;
+        Ok(source.parse()?)
+    }
+
+    /// The first logged day.
     pub fn start_date(&self) -> Date {
         self.start_date
     }
 
+    /// The last logged day.
+    pub fn end_date(&self) -> Date {
+        self.start_date + Duration::days(self.days.len() as i64 - 1)
+    }
+
+    /// The `[document]` table.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// The log's own `[style]` table.
+    pub fn style(&self) -> &style::Overrides {
+        &self.style
+    }
+
+    /// Every highlight, in the order they were declared.
+    pub fn highlights(&self) -> &[Highlight] {
+        &self.highlights
+    }
+
+    /// The highlight called `name`, if the log declares one.
+    pub fn highlight(&self, name: &str) -> Option<&Highlight> {
+        self.highlights
+            .iter()
+            .find(|highlight| highlight.name == name)
+    }
+
+    /// The highlight of every day in order, from [`Log::start_date`] on.
     pub fn days(&self) -> Days<'_> {
         Days {
             highlights: &self.highlights,
-            iter: self.days.iter(),
+            iter: self.days.days(),
+        }
+    }
+
+    /// Every run of consecutive days with the same highlight, or with none, in order.
+    pub fn runs(&self) -> impl Iterator<Item = Run<'_>> + '_ {
+        self.runs_in(..)
+    }
+
+    /// Every run of logged days within `dates` in order, with the runs at either end cut down to
+    /// fit. Takes logarithmic time to find the first run.
+    pub fn runs_in(&self, dates: impl RangeBounds<Date>) -> impl Iterator<Item = Run<'_>> + '_ {
+        self.days
+            .iter_in(self.indices(dates))
+            .map(|(range, day)| Run {
+                start: self.start_date + Duration::days(range.start as i64),
+                end: self.start_date + Duration::days(range.end as i64 - 1),
+                highlight: self.highlight_of(day),
+            })
+    }
+
+    /// The highlight of `date`: `None` if the date is outside the log, and `Some(None)` if it was
+    /// logged without a highlight. Takes logarithmic time.
+    pub fn get(&self, date: Date) -> Option<Option<&Highlight>> {
+        let i = usize::try_from((date - self.start_date).whole_days()).ok()?;
+        Some(self.highlight_of(self.days.get(i)?))
+    }
+
+    /// Every logged date within `dates` with its highlight, in order.
+    pub fn range(
+        &self,
+        dates: impl RangeBounds<Date>,
+    ) -> impl Iterator<Item = (Date, Option<&Highlight>)> + '_ {
+        let indices = self.indices(dates);
+        let start = self.start_date + Duration::days(indices.start as i64);
+        let days = self.days.days_from(indices.start).take(indices.len());
+        (0..)
+            .zip(days)
+            .map(move |(i, day)| (start + Duration::days(i), self.highlight_of(day)))
+    }
+
+    /// Every date with the same highlight as `highlight`, matched by name, in order.
+    pub fn dates_with(&self, highlight: &Highlight) -> impl Iterator<Item = Date> + '_ {
+        let i = (self.highlights.iter()).position(|declared| declared.name == highlight.name);
+        (self.days.iter())
+            .filter(move |&(_, day)| i.is_some() && day.highlight() == i)
+            .flat_map(move |(range, _)| range.map(|i| self.start_date + Duration::days(i as i64)))
+    }
+
+    fn highlight_of(&self, day: Day) -> Option<&Highlight> {
+        day.highlight().map(|i| &self.highlights[i])
+    }
+
+    /// The indices of the logged days within `dates`.
+    fn indices(&self, dates: impl RangeBounds<Date>) -> Range<usize> {
+        let len = self.days.len() as i64;
+        let index = |date: Date| (date - self.start_date).whole_days().clamp(-1, len);
+        let start = match dates.start_bound() {
+            Bound::Included(&date) => index(date),
+            Bound::Excluded(&date) => index(date) + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match dates.end_bound() {
+            Bound::Included(&date) => index(date) + 1,
+            Bound::Excluded(&date) => index(date),
+            Bound::Unbounded => len,
+        };
+        let start = start.clamp(0, len) as usize;
+        start..(end.clamp(0, len) as usize).max(start)
+    }
+
+    /// The label and note given to `date`, if any.
+    pub fn annotation(&self, date: Date) -> Option<&Annotation> {
+        self.annotations.get(&date)
+    }
+
+    /// The name of the public holiday on `date` in any of the log's `holidays` regions.
+    pub fn holiday(&self, date: Date) -> Option<&str> {
+        self.holidays.get(&date).map(String::as_str)
+    }
+
+    /// Whether the log asks for any public holidays at all.
+    pub fn has_holidays(&self) -> bool {
+        !self.regions.is_empty()
+    }
+
+    /// A copy of the log cut down to the days between `from` and `to` inclusive, or `None` if no
+    /// logged days are in that range.
+    pub fn between(&self, from: Option<Date>, to: Option<Date>) -> Option<Log> {
+        let from = from.map_or(self.start_date, |from| from.max(self.start_date));
+        let to = to.map_or(self.end_date(), |to| to.min(self.end_date()));
+        if from > to {
+            return None;
+        }
+        let skip = usize::try_from((from - self.start_date).whole_days()).unwrap();
+        let len = usize::try_from((to - from).whole_days()).unwrap() + 1;
+        Some(Log {
+            regions: self.regions.clone(),
+            document: self.document.clone(),
+            style: self.style.clone(),
+            highlights: self.highlights.clone(),
+            start_date: from,
+            days: self.days.slice(skip..skip + len),
+            annotations: (self.annotations.range(from..=to))
+                .map(|(&date, annotation)| (date, annotation.clone()))
+                .collect(),
+            holidays: self.holidays.clone(),
+        })
+    }
+
+    /// Write the log back out in the format it is parsed from, such that parsing the result gives
+    /// an equal log. Every section comes in its usual order, and every day on its own line.
+    pub fn to_toml(&self) -> String {
+        #[derive(Serialize)]
+        struct Header<'log> {
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            holidays: &'log [holidays::Region],
+            #[serde(skip_serializing_if = "Option::is_none")]
+            document: Option<&'log Document>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            style: Option<&'log style::Overrides>,
+        }
+        let header = Header {
+            holidays: &self.regions,
+            document: Some(&self.document).filter(|&document| *document != Document::default()),
+            style: Some(&self.style).filter(|&style| *style != style::Overrides::default()),
+        };
+        let mut toml = toml::to_string(&header).expect("failed to serialize log header");
+        if !toml.is_empty() {
+            toml.push('\n');
+        }
+
+        toml.push_str("[highlights]\n");
+        for highlight in &self.highlights {
+            let shape = match highlight.shape {
+                Shape::Rectangle => "rectangle",
+                Shape::Circle => "circle",
+            };
+            let Colour([r, g, b]) = highlight.colour;
+            write!(
+                toml,
+                "{} = {{ shape = \"{shape}\", colour = \"#{r:02X}{g:02X}{b:02X}\"",
+                Key(&highlight.name),
+            )
+            .unwrap();
+            if let Some(symbol) = &highlight.symbol {
+                write!(toml, ", symbol = {}", Basic(symbol)).unwrap();
+            }
+            toml.push_str(" }\n");
+        }
+
+        toml.push_str("\n[data]\n");
+        let mut date = self.start_date;
+        for highlight in self.days() {
+            let name = highlight.map_or("", |highlight| &highlight.name);
+            let weekday = data::abbreviation(date.weekday());
+            write!(toml, "{date}.{weekday} = ").unwrap();
+            match self.annotation(date) {
+                None => write!(toml, "{}", Basic(name)).unwrap(),
+                Some(annotation) => {
+                    let mut fields = Vec::new();
+                    if highlight.is_some() {
+                        fields.push(format!("highlight = {}", Basic(name)));
+                    }
+                    if let Some(label) = &annotation.label {
+                        fields.push(format!("label = {}", Basic(label)));
+                    }
+                    if let Some(note) = &annotation.note {
+                        fields.push(format!("note = {}", Basic(note)));
+                    }
+                    write!(toml, "{{ {} }}", fields.join(", ")).unwrap();
+                }
+            }
+            toml.push('\n');
+            date = date.next_day().unwrap();
+        }
+        toml
+    }
+}
+
+/// A TOML key, quoted only if it has to be.
+struct Key<'s>(&'s str);
+
+impl Display for Key<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let bare = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if !self.0.is_empty() && self.0.chars().all(bare) {
+            f.write_str(self.0)
+        } else {
+            Basic(self.0).fmt(f)
+        }
+    }
+}
+
+/// A TOML basic string, which keeps to one line.
+struct Basic<'s>(&'s str);
+
+impl Display for Basic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\t' => f.write_str("\\t")?,
+                '\r' => f.write_str("\\r")?,
+                c if c.is_control() => write!(f, "\\u{:04X}", u32::from(c))?,
+                c => f.write_char(c)?,
+            }
         }
+        f.write_char('"')
     }
 }
 
+/// An iterator over the highlight of every logged day. See [`Log::days`].
 #[derive(Debug)]
-pub(crate) struct Days<'log> {
+pub struct Days<'log> {
     highlights: &'log [Highlight],
-    iter: slice::Iter<'log, Day>,
+    iter: runs::Days<'log>,
 }
 
 impl<'log> Iterator for Days<'log> {
@@ -65,6 +407,33 @@ impl ExactSizeIterator for Days<'_> {
     }
 }
 
+/// Consecutive days with the same highlight, or with none. See [`Log::runs`].
+#[derive(Debug, Clone, Copy)]
+pub struct Run<'log> {
+    start: Date,
+    end: Date,
+    highlight: Option<&'log Highlight>,
+}
+
+impl<'log> Run<'log> {
+    /// The first day.
+    pub fn start(&self) -> Date {
+        self.start
+    }
+    /// The last day, inclusive.
+    pub fn end(&self) -> Date {
+        self.end
+    }
+    /// The highlight of every day in the run, or `None` if they were logged without one.
+    pub fn highlight(&self) -> Option<&'log Highlight> {
+        self.highlight
+    }
+    /// The number of days in the run.
+    pub fn days(&self) -> u32 {
+        u32::try_from((self.end - self.start).whole_days()).unwrap() + 1
+    }
+}
+
 struct DeVisitor;
 
 impl<'de> de::Visitor<'de> for DeVisitor {
@@ -73,19 +442,66 @@ impl<'de> de::Visitor<'de> for DeVisitor {
         f.write_str("a table")
     }
     fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
-        let index: HighlightIndex = de_map_access_require_entry(&mut map, "highlights")?;
+        // Everything before `[highlights]` is optional, but has to come in this order if present.
+        let mut regions = Vec::new();
+        let mut document = Document::default();
+        let mut style = style::Overrides::default();
+        let mut key = map.next_key::<String>()?;
+        if key.as_deref() == Some("holidays") {
+            regions = map.next_value()?;
+            key = map.next_key()?;
+        }
+        if key.as_deref() == Some("document") {
+            document = map.next_value()?;
+            key = map.next_key()?;
+        }
+        if key.as_deref() == Some("style") {
+            style = map.next_value()?;
+            key = map.next_key()?;
+        }
+        match key.as_deref() {
+            Some("highlights") => {}
+            Some(key) => {
+                let fields = &["holidays", "document", "style", "highlights"];
+                return Err(de::Error::unknown_field(key, fields));
+            }
+            None => return Err(de::Error::missing_field("highlights")),
+        }
+        let index = map.next_value::<HighlightIndex>()?;
+
         let seed = data::DeserializeSeed {
             indices: &index.indices,
         };
         let data = de_map_access_require_entry_seed(&mut map, "data", seed)?;
+        let holidays = holidays_around(&regions, data.start_date, data.days.len());
         Ok(Log {
+            regions,
+            document,
+            style,
             highlights: index.highlights,
             start_date: data.start_date,
             days: data.days,
+            annotations: data.annotations,
+            holidays,
         })
     }
 }
 
+/// The public holidays of `regions` from the start of the year before the `len` days from
+/// `start_date` to the end of the year after them.
+fn holidays_around(
+    regions: &[holidays::Region],
+    start_date: Date,
+    len: usize,
+) -> BTreeMap<Date, String> {
+    let end_date = start_date + Duration::days(len as i64 - 1);
+    holidays::between(
+        regions,
+        Date::from_ordinal_date(start_date.year() - 1, 1).unwrap(),
+        Date::from_calendar_date(end_date.year() + 1, Month::December, 31).unwrap(),
+    )
+}
+
 struct HighlightIndex {
     highlights: Vec<Highlight>,
     indices: ahash::HashMap<String, usize>,
@@ -108,10 +524,17 @@ impl<'de> de::Visitor<'de> for HighlightsVisitor {
             highlights: Vec::new(),
             indices: HashMap::default(),
         };
-        while let Some((key, value)) = map.next_entry()? {
+        while let Some((key, mut value)) = map.next_entry::<String, Highlight>()? {
             if index.indices.contains_key(&key) {
                 return Err(de::Error::custom(format_args!("duplicate highlight {key}")));
             }
+            if index.highlights.len() == Day::MAX_HIGHLIGHTS {
+                let max = Day::MAX_HIGHLIGHTS;
+                return Err(de::Error::custom(format_args!(
+                    "more than {max} highlights"
+                )));
+            }
+            value.name = key.clone();
             index.indices.insert(key, index.highlights.len());
             index.highlights.push(value);
         }
@@ -119,23 +542,58 @@ impl<'de> de::Visitor<'de> for HighlightsVisitor {
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// A kind of day that can be logged, from the `[highlights]` table.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub(crate) struct Highlight {
-    pub shape: Shape,
-    pub colour: Colour,
+pub struct Highlight {
+    #[serde(skip)]
+    name: String,
+    shape: Shape,
+    colour: Colour,
+    symbol: Option<String>,
+}
+
+impl Highlight {
+    /// The key in the `[highlights]` table, which is what days are logged with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// How the highlighted days are marked.
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+    /// The colour of the shape.
+    pub fn colour(&self) -> Colour {
+        self.colour
+    }
+    /// Marks highlighted days in text output, where colours aren't available. Often an emoji.
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+/// How a highlighted day is marked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
-pub(crate) enum Shape {
+#[non_exhaustive]
+pub enum Shape {
+    /// Fills the whole cell, so that consecutive days join up.
     Rectangle,
+    /// A circle around the day number.
     Circle,
 }
 
 mod colour {
-    #[derive(Debug)]
-    pub(crate) struct Colour(pub [u8; 3]);
+    /// An RGB colour, written as `"#RRGGBB"`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Colour(pub [u8; 3]);
+
+    impl Serialize for Colour {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let [r, g, b] = self.0;
+            serializer.collect_str(&format_args!("#{r:02X}{g:02X}{b:02X}"))
+        }
+    }
 
     impl<'de> Deserialize<'de> for Colour {
         fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
@@ -197,19 +655,22 @@ mod colour {
     use serde::de;
     use serde::Deserialize;
     use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
     use std::fmt;
     use std::fmt::Formatter;
     use std::simd::simd_swizzle;
     use std::simd::Simd;
     use std::simd::SimdPartialOrd as _;
 }
-pub(crate) use colour::Colour;
+pub use colour::Colour;
 
 mod data {
     #[derive(Debug)]
     pub(super) struct Data {
         pub start_date: Date,
-        pub days: Vec<Day>,
+        pub days: Runs,
+        pub annotations: BTreeMap<Date, Annotation>,
     }
 
     pub(super) struct DeserializeSeed<'map, S: BuildHasher> {
@@ -236,19 +697,28 @@ mod data {
                 .next_key::<Date>()?
                 .ok_or_else(|| de::Error::invalid_length(0, &"a non-empty table"))?;
             let mut current_date = start_date;
-            let mut days = Vec::new();
+            let mut days = Runs::default();
+            let mut annotations = BTreeMap::new();
             loop {
-                days.push(map.next_value_seed(WrappedDay {
+                let (day, annotation) = map.next_value_seed(WrappedDay {
                     indices: self.indices,
                     date: current_date,
-                })?);
+                })?;
+                days.push(day);
+                if annotation.label.is_some() || annotation.note.is_some() {
+                    annotations.insert(current_date, annotation);
+                }
                 current_date = current_date.next_day().unwrap();
                 match map.next_key_seed(Exact(current_date))? {
                     Some(()) => {}
                     None => break,
                 }
             }
-            Ok(Data { start_date, days })
+            Ok(Data {
+                start_date,
+                days,
+                annotations,
+            })
         }
     }
 
@@ -258,7 +728,7 @@ mod data {
     }
 
     impl<'de, S: BuildHasher> serde::de::DeserializeSeed<'de> for WrappedDay<'_, S> {
-        type Value = Day;
+        type Value = (Day, Annotation);
         fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
         where
             D: Deserializer<'de>,
@@ -268,32 +738,40 @@ mod data {
     }
 
     impl<'de, S: BuildHasher> de::Visitor<'de> for WrappedDay<'_, S> {
-        type Value = Day;
+        type Value = (Day, Annotation);
         fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
             f.write_str("a map")
         }
         fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
-            map.next_key_seed(LiteralStr(match self.date.weekday() {
-                Weekday::Monday => "Mon",
-                Weekday::Tuesday => "Tue",
-                Weekday::Wednesday => "Wed",
-                Weekday::Thursday => "Thu",
-                Weekday::Friday => "Fri",
-                Weekday::Saturday => "Sat",
-                Weekday::Sunday => "Sun",
-            }))?;
+            map.next_key_seed(LiteralStr(abbreviation(self.date.weekday())))?;
             map.next_value_seed(day::DeserializeSeed {
                 indices: self.indices,
             })
         }
     }
 
+    /// How weekdays are written after the date in keys of the data table.
+    pub(super) fn abbreviation(weekday: Weekday) -> &'static str {
+        match weekday {
+            Weekday::Monday => "Mon",
+            Weekday::Tuesday => "Tue",
+            Weekday::Wednesday => "Wed",
+            Weekday::Thursday => "Thu",
+            Weekday::Friday => "Fri",
+            Weekday::Saturday => "Sat",
+            Weekday::Sunday => "Sun",
+        }
+    }
+
     use super::day;
     use super::util::Exact;
     use super::util::LiteralStr;
+    use super::Annotation;
     use super::Day;
+    use crate::runs::Runs;
     use serde::de;
     use serde::Deserializer;
+    use std::collections::BTreeMap;
     use std::collections::HashMap;
     use std::fmt;
     use std::fmt::Formatter;
@@ -303,26 +781,95 @@ mod data {
 }
 
 mod day {
-    #[derive(Debug, Clone, Copy)]
+    /// The highlight of a day, as an index into the log's highlights.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub(crate) struct Day {
-        // `usize::MAX` if there is no highlight
-        highlight: usize,
+        // `u16::MAX` if there is no highlight
+        highlight: u16,
     }
 
     impl Day {
+        /// The most highlights a log can have.
+        pub(crate) const MAX_HIGHLIGHTS: usize = u16::MAX as usize;
+
+        pub(crate) fn new(highlight: Option<usize>) -> Self {
+            let highlight = highlight.map_or(u16::MAX, |i| {
+                u16::try_from(i)
+                    .ok()
+                    .filter(|&i| i != u16::MAX)
+                    .expect("too many highlights")
+            });
+            Self { highlight }
+        }
+
         pub(crate) fn highlight(self) -> Option<usize> {
-            if self.highlight == usize::MAX {
+            if self.highlight == u16::MAX {
                 None
             } else {
-                Some(self.highlight)
+                Some(usize::from(self.highlight))
             }
         }
     }
 
+    /// Free-form text attached to a day.
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub struct Annotation {
+        /// A short title for the day.
+        pub label: Option<String>,
+        /// A longer description of the day.
+        pub note: Option<String>,
+    }
+
+    /// Accepts either the name of a highlight, or a table with an optional highlight, label and
+    /// note.
     pub(super) struct DeserializeSeed<'map, S: BuildHasher> {
         pub indices: &'map HashMap<String, usize, S>,
     }
     impl<'de, S: BuildHasher> serde::de::DeserializeSeed<'de> for DeserializeSeed<'_, S> {
+        type Value = (Day, Annotation);
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+    }
+    impl<'de, S: BuildHasher> de::Visitor<'de> for DeserializeSeed<'_, S> {
+        type Value = (Day, Annotation);
+        fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str("a string or table")
+        }
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            let highlight = HighlightSeed {
+                indices: self.indices,
+            };
+            Ok((highlight.visit_str(v)?, Annotation::default()))
+        }
+        fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            const FIELDS: &[&str] = &["highlight", "label", "note"];
+            let mut day = Day::new(None);
+            let mut annotation = Annotation::default();
+            while let Some(key) = map.next_key::<String>()? {
+                match &*key {
+                    "highlight" => {
+                        day = map.next_value_seed(HighlightSeed {
+                            indices: self.indices,
+                        })?;
+                    }
+                    "label" => annotation.label = Some(map.next_value()?),
+                    "note" => annotation.note = Some(map.next_value()?),
+                    _ => return Err(de::Error::unknown_field(&key, FIELDS)),
+                }
+            }
+            Ok((day, annotation))
+        }
+    }
+
+    struct HighlightSeed<'map, S: BuildHasher> {
+        indices: &'map HashMap<String, usize, S>,
+    }
+    impl<'de, S: BuildHasher> serde::de::DeserializeSeed<'de> for HighlightSeed<'_, S> {
         type Value = Day;
         fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
         where
@@ -331,22 +878,20 @@ mod day {
             deserializer.deserialize_str(self)
         }
     }
-    impl<'de, S: BuildHasher> de::Visitor<'de> for DeserializeSeed<'_, S> {
+    impl<'de, S: BuildHasher> de::Visitor<'de> for HighlightSeed<'_, S> {
         type Value = Day;
         fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
             f.write_str("a string")
         }
         fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
             if v.is_empty() {
-                return Ok(Day {
-                    highlight: usize::MAX,
-                });
+                return Ok(Day::new(None));
             }
             let highlight = *self
                 .indices
                 .get(v)
                 .ok_or_else(|| E::custom(format_args!("no known highlight `{v}`")))?;
-            Ok(Day { highlight })
+            Ok(Day::new(Some(highlight)))
         }
     }
 
@@ -357,20 +902,10 @@ mod day {
     use std::fmt::Formatter;
     use std::hash::BuildHasher;
 }
+pub use day::Annotation;
 pub(crate) use day::Day;
 
 mod util {
-    pub(crate) fn de_map_access_require_entry<'de, T, A>(
-        map: &mut A,
-        key: &'static str,
-    ) -> Result<T, A::Error>
-    where
-        T: Deserialize<'de>,
-        A: de::MapAccess<'de>,
-    {
-        de_map_access_require_entry_seed(map, key, PhantomData::<T>)
-    }
-
     pub(crate) fn de_map_access_require_entry_seed<'de, S, A>(
         map: &mut A,
         key: &'static str,
@@ -451,20 +986,411 @@ mod util {
 
     use serde::de;
     use serde::de::DeserializeSeed;
-    use serde::Deserialize;
-    use std::marker::PhantomData;
 }
-use util::de_map_access_require_entry;
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_error_snippet() {
+        let source = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2022-02-01.Tue = \"b\"\n";
+        let error = source.parse::<Log>().unwrap_err();
+        assert_eq!(
+            error.snippet(source, "log.toml").to_string(),
+            "error: no known highlight `b`\n \
+            --> log.toml:4:18\n  \
+            |\n\
+            4 | 2022-02-01.Tue = \"b\"\n  \
+            |                  ^^^\n",
+        );
+
+        let source = "[highlights]\n";
+        let error = source.parse::<Log>().unwrap_err();
+        assert_eq!(error.span, source.len()..source.len());
+    }
+
+    #[test]
+    fn from_reader() {
+        let source = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2022-02-01.Tue = \"a\"\n";
+        let log = Log::from_reader(source.as_bytes()).unwrap();
+        assert_eq!(log.days().len(), 1);
+
+        let error = Log::from_reader(&b"[data]\n"[..]).unwrap_err();
+        assert!(matches!(error, Error::Parse(_)));
+    }
+
+    #[test]
+    fn runs() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2022-02-01.Tue = \"a\"\n\
+            2022-02-02.Wed = \"a\"\n\
+            2022-02-03.Thu = \"\"\n\
+            2022-02-04.Fri = { highlight = \"a\", label = \"Trip\" }\n"
+            .parse::<Log>()
+            .unwrap();
+        let runs = (log.runs())
+            .map(|run| (run.start().day(), run.days(), run.highlight().is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(runs, [(1, 2, true), (3, 1, false), (4, 1, true)]);
+
+        let date = |day| Date::from_calendar_date(2022, Month::February, day).unwrap();
+        assert!(log.get(date(2)).unwrap().is_some());
+        assert!(log.get(date(3)).unwrap().is_none());
+        assert!(log.get(date(5)).is_none());
+        assert!(log
+            .get(Date::from_calendar_date(2022, Month::January, 31).unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn queries() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            b = { shape = \"rectangle\", colour = \"#FF0000\" }\n\
+            [data]\n\
+            2022-02-01.Tue = \"a\"\n\
+            2022-02-02.Wed = \"b\"\n\
+            2022-02-03.Thu = \"\"\n\
+            2022-02-04.Fri = \"a\"\n\
+            2022-02-05.Sat = \"a\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let date = |day| Date::from_calendar_date(2022, Month::February, day).unwrap();
+        assert_eq!(log.end_date(), date(5));
+
+        let a = log.highlight("a").unwrap();
+        assert_eq!(a.name, "a");
+        assert!(log.highlight("c").is_none());
+        let dates = log.dates_with(a).collect::<Vec<_>>();
+        assert_eq!(dates, [date(1), date(4), date(5)]);
+        let b = log.highlight("b").unwrap();
+        assert_eq!(log.dates_with(b).collect::<Vec<_>>(), [date(2)]);
+
+        fn names(dates: Vec<(Date, Option<&Highlight>)>) -> Vec<(u8, &str)> {
+            (dates.into_iter())
+                .map(|(date, highlight)| (date.day(), highlight.map_or("", |h| &*h.name)))
+                .collect()
+        }
+        let range = log.range(date(2)..=date(4)).collect();
+        assert_eq!(names(range), [(2, "b"), (3, ""), (4, "a")]);
+        let range = log.range(date(4)..).collect();
+        assert_eq!(names(range), [(4, "a"), (5, "a")]);
+        let range = log.range(..date(2)).collect();
+        assert_eq!(names(range), [(1, "a")]);
+        let january = Date::from_calendar_date(2022, Month::January, 1).unwrap();
+        assert_eq!(log.range(january..=date(1)).count(), 1);
+        assert_eq!(log.range(..).count(), 5);
+        assert_eq!(log.range(date(6)..).count(), 0);
+        assert_eq!(log.range(date(4)..date(2)).count(), 0);
+    }
+
+    #[test]
+    fn annotated_days() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2022-02-01.Tue = { highlight = \"a\", label = \"Trip\", note = \"Went <home>\" }\n\
+            2022-02-02.Wed = { label = \"Rest\" }\n\
+            2022-02-03.Thu = {}\n"
+            .parse::<Log>()
+            .unwrap();
+        let days = log.days().map(|day| day.is_some()).collect::<Vec<_>>();
+        assert_eq!(days, [true, false, false]);
+
+        let date = log.start_date();
+        let annotation = log.annotation(date).unwrap();
+        assert_eq!(annotation.label.as_deref(), Some("Trip"));
+        assert_eq!(annotation.note.as_deref(), Some("Went <home>"));
+        let annotation = log.annotation(date.next_day().unwrap()).unwrap();
+        assert_eq!(annotation.label.as_deref(), Some("Rest"));
+        assert!(annotation.note.is_none());
+        assert!(log.annotation(date + Duration::days(2)).is_none());
+
+        let source = "[highlights]\n\
+            [data]\n\
+            2022-02-01.Tue = { colour = \"a\" }\n";
+        let error = source.parse::<Log>().unwrap_err();
+        assert!(error.message.starts_with("unknown field `colour`"));
+    }
+
+    #[test]
+    fn document_table() {
+        let log = "holidays = [\"US\"]\n\
+            [document]\n\
+            title = \"Running\"\n\
+            keywords = [\"running\", \"habits\"]\n\
+            [highlights]\n\
+            [data]\n\
+            2022-02-01.Tue = \"\"\n"
+            .parse::<Log>()
+            .unwrap();
+        assert_eq!(log.document().title(), "Running");
+        assert_eq!(log.document().author, None);
+        assert_eq!(log.document().keywords, ["running", "habits"]);
+
+        let source = "[highlights]\n[data]\n2022-02-01.Tue = \"\"\n";
+        assert_eq!(
+            source.parse::<Log>().unwrap().document().title(),
+            "Calendar"
+        );
+    }
+
+    #[test]
+    fn style_table() {
+        let log = "[style]\n\
+            day_size = 12.0\n\
+            [highlights]\n\
+            [data]\n\
+            2022-02-01.Tue = \"\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let style = Style::default().with(log.style());
+        assert_eq!(style.day_size, 12.0);
+        assert_eq!(style.title_size, Style::default().title_size);
+
+        let source = "[style]\n\
+            day_colour = \"#000000\"\n\
+            [highlights]\n";
+        let error = source.parse::<Log>().unwrap_err();
+        assert!(error.message.starts_with("unknown field `day_colour`"));
+    }
+
+    #[test]
+    fn between_dates() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2022-12-31.Sat = \"a\"\n\
+            2023-01-01.Sun = { highlight = \"a\", label = \"New year\" }\n\
+            2023-01-02.Mon = \"\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let jan_1 = log.start_date().next_day().unwrap();
+
+        let window = log.between(Some(jan_1), None).unwrap();
+        assert_eq!(window.start_date(), jan_1);
+        assert_eq!(window.days().len(), 2);
+        assert!(window.annotation(jan_1).is_some());
+
+        let window = log.between(None, Some(log.start_date())).unwrap();
+        assert_eq!(window.days().len(), 1);
+        assert!(window.annotation(jan_1).is_none());
+
+        let late = jan_1 + Duration::days(7);
+        assert!(log.between(Some(late), None).is_none());
+    }
+
+    #[test]
+    fn holiday_regions() {
+        let log = "holidays = [\"GB-ENG\", \"DE-BY\"]\n\
+            [highlights]\n\
+            [data]\n\
+            2022-12-31.Sat = \"\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let date = |s| Date::parse(s, &Iso8601::DEFAULT).unwrap();
+        assert_eq!(
+            log.holiday(date("2022-12-27")),
+            Some("Christmas Day (substitute day)")
+        );
+        assert_eq!(log.holiday(date("2023-01-06")), Some("Epiphany"));
+        assert_eq!(log.holiday(date("2022-12-28")), None);
+
+        let source = "holidays = [\"GB\"]\n";
+        let error = source.parse::<Log>().unwrap_err();
+        assert!(error.message.starts_with("unknown variant `GB`"));
+        assert_eq!(error.span, 11..17);
+    }
+
+    #[test]
+    fn to_toml() {
+        let source = "holidays = [\"GB-ENG\"]\n\
+            \n\
+            [document]\n\
+            title = \"Running \\\"2022\\\"\"\n\
+            \n\
+            [style]\n\
+            first_month = 9\n\
+            non_working_days = [\"Fri\", \"Sat\"]\n\
+            \n\
+            [highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            \"b c\" = { shape = \"rectangle\", colour = \"#0000FF\", symbol = \"🔵\" }\n\
+            \n\
+            [data]\n\
+            2022-02-01.Tue = \"a\"\n\
+            2022-02-02.Wed = \"\"\n\
+            2022-02-03.Thu = { highlight = \"b c\", label = \"Trip\" }\n\
+            2022-02-04.Fri = { note = \"Two\\nlines\" }\n";
+        let log = source.parse::<Log>().unwrap();
+        assert_eq!(log.to_toml(), source);
+    }
+
+    #[test]
+    fn to_toml_round_trips() {
+        let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+        for _ in 0..500 {
+            let log = arbitrary_log(&mut rng);
+            let toml = log.to_toml();
+            let parsed = (toml.parse::<Log>()).unwrap_or_else(|e| panic!("{e}:\n{toml}"));
+            assert_eq!(parsed, log, "{toml}");
+            assert_eq!(parsed.to_toml(), toml);
+        }
+    }
+
+    /// A log with random contents, including the awkward characters that need escaping.
+    fn arbitrary_log(rng: &mut Rng) -> Log {
+        let regions = [Region::GbEng, Region::Us, Region::DeBy];
+        let regions = regions
+            .into_iter()
+            .filter(|_| rng.chance())
+            .collect::<Vec<_>>();
+
+        let document = Document {
+            title: rng.chance().then(|| rng.string()),
+            author: rng.chance().then(|| rng.string()),
+            subject: rng.chance().then(|| rng.string()),
+            keywords: (0..rng.below(3)).map(|_| rng.string()).collect(),
+        };
+
+        let non_working_days = ["[]", "[\"Sun\"]", "[\"Fri\", \"Sat\"]"][rng.below(3)];
+        let style_lines = [
+            "first_month = 9".to_owned(),
+            "day_size = 12.5".to_owned(),
+            "page_width = 297.0".to_owned(),
+            "background = \"#1E1E1E\"".to_owned(),
+            "week_start = \"Sun\"".to_owned(),
+            "week_numbers = \"iso\"".to_owned(),
+            format!("non_working_days = {non_working_days}"),
+            "regular_font = \"fonts/Regular \\\"1\\\".ttf\"".to_owned(),
+        ];
+        let style = (style_lines.iter())
+            .filter(|_| rng.chance())
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        let style = toml::from_str::<style::Overrides>(&style).unwrap();
+
+        let highlights = (0..rng.below(4))
+            .map(|i| Highlight {
+                // The index keeps names unique, and ending in it keeps them from being empty.
+                name: format!("{}{i}", rng.string()),
+                shape: if rng.chance() {
+                    Shape::Circle
+                } else {
+                    Shape::Rectangle
+                },
+                colour: Colour([rng.next() as u8, rng.next() as u8, rng.next() as u8]),
+                symbol: rng.chance().then(|| rng.string()),
+            })
+            .collect::<Vec<_>>();
+
+        let start_date = Date::from_julian_day(2_415_021 + rng.below(73_000) as i32).unwrap();
+        let len = 1 + rng.below(60);
+        let days = (0..len)
+            // Zero is an unhighlighted day.
+            .map(|_| Day::new(rng.below(highlights.len() + 1).checked_sub(1)))
+            .collect();
+        let mut annotations = BTreeMap::new();
+        for _ in 0..rng.below(4) {
+            let date = start_date + Duration::days(rng.below(len) as i64);
+            let annotation = Annotation {
+                label: rng.chance().then(|| rng.string()),
+                note: rng.chance().then(|| rng.string()),
+            };
+            if annotation != Annotation::default() {
+                annotations.insert(date, annotation);
+            }
+        }
+
+        Log {
+            holidays: holidays_around(&regions, start_date, len),
+            regions,
+            document,
+            style,
+            highlights,
+            start_date,
+            days,
+            annotations,
+        }
+    }
+
+    /// A xorshift generator, which is plenty for making test cases.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn chance(&mut self) -> bool {
+            self.next() & 1 == 0
+        }
+
+        fn string(&mut self) -> String {
+            const CHARS: &[char] = &[
+                'a', 'Z', '0', ' ', '"', '\'', '\\', '\n', '\r', '\t', '\u{7}', '\u{7F}', '#', '=',
+                '.', '[', 'é', '🔵',
+            ];
+            (0..self.below(6))
+                .map(|_| CHARS[self.below(CHARS.len())])
+                .collect()
+        }
+    }
+
+    use super::holidays_around;
+    use crate::holidays::Region;
+    use crate::log::Annotation;
+    use crate::log::Colour;
+    use crate::log::Day;
+    use crate::log::Document;
+    use crate::log::Highlight;
+    use crate::log::Log;
+    use crate::log::Shape;
+    use crate::style;
+    use crate::style::Style;
+    use crate::Error;
+    use std::collections::BTreeMap;
+    use time::format_description::well_known::Iso8601;
+    use time::Date;
+    use time::Duration;
+    use time::Month;
+}
 
 use self::util::de_map_access_require_entry_seed;
+use crate::holidays;
+use crate::runs;
+use crate::runs::Runs;
+use crate::style;
 use serde::de;
 use serde::Deserialize;
 use serde::Deserializer;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
-use std::slice;
+use std::fmt::Write as _;
+use std::io::Read;
+use std::ops::Bound;
+use std::ops::Range;
+use std::ops::RangeBounds;
 use std::str::FromStr;
 use time::Date;
+use time::Duration;
+use time::Month;
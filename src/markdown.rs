@@ -0,0 +1,186 @@
+//! Markdown output.
+
+/// Write a legend followed by a table for every month of the log. With `as_of`, that day's number
+/// is marked with brackets and the numbers after it are in italics.
+pub fn write(
+    log: &Log,
+    style: &Style,
+    as_of: Option<Date>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let highlights = log.highlights();
+    let symbols = terminal::symbols(highlights);
+
+    writeln!(out, "# {}", Escape(log.document().title()))?;
+    writeln!(out)?;
+    writeln!(out, "| Symbol | Highlight |")?;
+    writeln!(out, "| --- | --- |")?;
+    for (highlight, symbol) in highlights.iter().zip(&symbols) {
+        writeln!(out, "| {} | {} |", Escape(symbol), Escape(highlight.name()))?;
+    }
+    if log.has_holidays() {
+        writeln!(out, "| **bold** | Public holiday |")?;
+    }
+    if let Some(as_of) = as_of {
+        writeln!(out, "| \\[day\\] | As of {as_of} |")?;
+        writeln!(out, "| *day* | Later |")?;
+    }
+
+    let week = style.week_start.week();
+    for page_year in PageYear::all(log, style.first_month.0) {
+        writeln!(out)?;
+        writeln!(out, "## {}", page_year.title())?;
+        for (year, month) in page_year.months() {
+            writeln!(out)?;
+            writeln!(out, "### {month}")?;
+            writeln!(out)?;
+            for weekday in week {
+                write!(out, "| {} ", &weekday.to_string()[..2])?;
+            }
+            writeln!(out, "|")?;
+            writeln!(out, "| ---: | ---: | ---: | ---: | ---: | ---: | ---: |")?;
+
+            let first = Date::from_calendar_date(year, month, 1).unwrap();
+            let mut column = style.week_start.position(first.weekday());
+            write!(out, "|{}", " |".repeat(usize::from(column)))?;
+            let days = time::util::days_in_year_month(year, month);
+            let last = Date::from_calendar_date(year, month, days).unwrap();
+            let mut logged = log.range(first..=last).peekable();
+            for day in 1..=days {
+                let date = Date::from_calendar_date(year, month, day).unwrap();
+                let highlight = (logged.next_if(|&(logged, _)| logged == date))
+                    .and_then(|(_, highlight)| highlight);
+                let mut day = day.to_string();
+                match as_of {
+                    Some(as_of) if date == as_of => day = format!("\\[{day}\\]"),
+                    Some(as_of) if date > as_of => day = format!("*{day}*"),
+                    _ => {}
+                }
+                if log.holiday(date).is_some() {
+                    day = format!("**{day}**");
+                }
+                match highlight {
+                    Some(highlight) => {
+                        let i = (0..highlights.len())
+                            .find(|&i| ptr::eq(&highlights[i], highlight))
+                            .unwrap();
+                        write!(out, " {day} {} |", Escape(&symbols[i]))?;
+                    }
+                    None => write!(out, " {day} |")?,
+                }
+                column += 1;
+                if column == 7 {
+                    writeln!(out)?;
+                    column = 0;
+                    if date != last {
+                        write!(out, "|")?;
+                    }
+                }
+            }
+            if column != 0 {
+                writeln!(out, "{}", " |".repeat(usize::from(7 - column)))?;
+            }
+
+            let holidays = (1..=time::util::days_in_year_month(year, month)).filter_map(|day| {
+                let date = Date::from_calendar_date(year, month, day).unwrap();
+                Some((day, log.holiday(date)?))
+            });
+            let mut holidays = holidays.peekable();
+            if holidays.peek().is_some() {
+                writeln!(out)?;
+                for (day, name) in holidays {
+                    writeln!(out, "- {day} {month}: {}", Escape(name))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Escapes characters that Markdown would otherwise treat as formatting.
+struct Escape<'s>(&'s str);
+
+impl Display for Escape<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            if "\\`*_[]<>|#".contains(c) {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn month_table() {
+        let log = "[highlights]\n\
+            run = { shape = \"circle\", colour = \"#00FF00\", symbol = \"🟢\" }\n\
+            rest_day = { shape = \"rectangle\", colour = \"#C0C0C0\" }\n\
+            [data]\n\
+            2023-12-30.Sat = \"rest_day\"\n\
+            2023-12-31.Sun = \"run\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let mut out = Vec::new();
+        write(&log, &Style::default(), None, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(
+            "# Calendar\n\
+            \n\
+            | Symbol | Highlight |\n\
+            | --- | --- |\n\
+            | 🟢 | run |\n\
+            | r | rest\\_day |\n\
+            \n\
+            ## 2023\n"
+        ));
+        assert!(out.ends_with(
+            "| Mo | Tu | We | Th | Fr | Sa | Su |\n\
+            | ---: | ---: | ---: | ---: | ---: | ---: | ---: |\n\
+            | | | | | 1 | 2 | 3 |\n\
+            | 4 | 5 | 6 | 7 | 8 | 9 | 10 |\n\
+            | 11 | 12 | 13 | 14 | 15 | 16 | 17 |\n\
+            | 18 | 19 | 20 | 21 | 22 | 23 | 24 |\n\
+            | 25 | 26 | 27 | 28 | 29 | 30 r | 31 🟢 |\n"
+        ));
+    }
+
+    #[test]
+    fn as_of() {
+        let log = "[highlights]\n\
+            run = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2023-12-30.Sat = \"run\"\n\
+            2023-12-31.Sun = \"run\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let as_of = Date::from_calendar_date(2023, Month::December, 29).unwrap();
+        let mut out = Vec::new();
+        write(&log, &Style::default(), Some(as_of), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("| \\[day\\] | As of 2023-12-29 |\n| *day* | Later |\n"));
+        assert!(out.ends_with("| 25 | 26 | 27 | 28 | \\[29\\] | *30* r | *31* r |\n"));
+    }
+
+    use super::write;
+    use crate::log::Log;
+    use crate::style::Style;
+    use time::Date;
+    use time::Month;
+}
+
+use crate::draw::PageYear;
+use crate::log::Log;
+use crate::style::Style;
+use crate::terminal;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write;
+use std::ptr;
+use time::Date;
@@ -0,0 +1,376 @@
+//! PDF output, with bookmarks, page labels and links between pages.
+
+/// Options that only apply to PDF output.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Options {
+    /// Give every month a bookmark under its year's.
+    pub month_bookmarks: bool,
+}
+
+impl Options {
+    /// Set [`Options::month_bookmarks`].
+    pub fn month_bookmarks(mut self, month_bookmarks: bool) -> Self {
+        self.month_bookmarks = month_bookmarks;
+        self
+    }
+}
+
+/// Render the days of the log chosen by `options` to a PDF at `file`, with a bookmark and page label for every page.
+pub fn render(
+    log: &Log,
+    options: &draw::Options,
+    pdf_options: &Options,
+    file: &str,
+) -> Result<(), Error> {
+    crate::write_file(file, |out| write(log, options, pdf_options, out))
+}
+
+/// Render the days of the log chosen by `options` as a PDF to `out`.
+pub fn write(
+    log: &Log,
+    options: &draw::Options,
+    pdf_options: &Options,
+    mut out: impl Write,
+) -> Result<(), Error> {
+    let log = &crate::chosen_days(log, options)?;
+    let fonts = Fonts::load(&options.style(log))?;
+
+    let info = log.document();
+    // PDF/X, printpdf's default, embeds a CMYK colour profile that is far bigger than the
+    // calendar itself, so the document is left as plain PDF.
+    let conformance = PdfConformance::Custom(CustomPdfConformance::default());
+    let mut document = PdfDocument::empty(info.title()).with_conformance(conformance);
+    if let Some(author) = &info.author {
+        document = document.with_author(author);
+    }
+    if let Some(subject) = &info.subject {
+        document = document.with_subject(subject);
+    }
+    if !info.keywords.is_empty() {
+        document = document.with_keywords(info.keywords.clone());
+    }
+
+    let scene = draw::calendar(log, options, &fonts);
+    let mut surface = Surface {
+        document: &document,
+        fonts: [
+            add_font(&document, &fonts.regular, &scene)?,
+            add_font(&document, &fonts.bold, &scene)?,
+            add_font(&document, &fonts.italic, &scene)?,
+        ],
+        page: None,
+    };
+    scene.render(&mut surface);
+
+    document.check_for_errors().map_err(pdf_error)?;
+
+    // printpdf can only make a flat outline and has no page labels, so add those afterward.
+    let bytes = document.save_to_bytes().map_err(pdf_error)?;
+    let mut document = lopdf::Document::load_mem(&bytes).map_err(pdf_error)?;
+    finish(&mut document, &scene, info, pdf_options).map_err(pdf_error)?;
+
+    document.save_to(&mut out)?;
+    Ok(())
+}
+
+/// Add the outline, page labels and links, compress the streams, and redo the document information
+/// so that it isn't limited to ASCII.
+fn finish(
+    document: &mut lopdf::Document,
+    scene: &Scene,
+    info: &log::Document,
+    options: &Options,
+) -> lopdf::Result<()> {
+    let page_ids = document.get_pages().into_values().collect::<Vec<_>>();
+
+    let outline_id = document.new_object_id();
+    let mut years = Vec::new();
+    for (i, (page, &page_id)) in scene.pages.iter().zip(&page_ids).enumerate() {
+        // Month pages come under their year's bookmark rather than getting their own.
+        if page.detail_of.is_some() {
+            continue;
+        }
+        let id = document.new_object_id();
+        let mut year = Dictionary::from_iter([
+            ("Title", text_string(&page.title)),
+            ("Parent", outline_id.into()),
+            ("Dest", vec![page_id.into(), "Fit".into()].into()),
+        ]);
+        if options.month_bookmarks {
+            let months = (page.anchors.iter())
+                .map(|anchor| {
+                    let detail = (scene.pages.iter().zip(&page_ids)).find(|(detail, _)| {
+                        detail.detail_of == Some(i) && detail.title == anchor.name
+                    });
+                    let dest = if let Some((_, &detail_id)) = detail {
+                        vec![detail_id.into(), "Fit".into()]
+                    } else {
+                        let (x, y) = anchor.position;
+                        vec![
+                            page_id.into(),
+                            "XYZ".into(),
+                            Pt::from(x).0.into(),
+                            Pt::from(page.size.1 - y).0.into(),
+                            Object::Null,
+                        ]
+                    };
+                    let month = Dictionary::from_iter([
+                        ("Title", text_string(&anchor.name)),
+                        ("Parent", id.into()),
+                        ("Dest", dest.into()),
+                    ]);
+                    (document.new_object_id(), month)
+                })
+                .collect();
+            // Collapsed, so that only the years show at first.
+            add_outline_items(document, &mut year, months, false);
+        }
+        years.push((id, year));
+    }
+    let mut outline = Dictionary::from_iter([("Type", "Outlines".into())]);
+    add_outline_items(document, &mut outline, years, true);
+    document.objects.insert(outline_id, outline.into());
+
+    for (page, &page_id) in scene.pages.iter().zip(&page_ids) {
+        if page.links.is_empty() {
+            continue;
+        }
+        let annotations = (page.links.iter())
+            .map(|link| {
+                let (left, top, width, height) = link.rect;
+                let bottom = page.size.1 - top - height;
+                let rect = [left, bottom, left + width, bottom + height];
+                let annotation = Dictionary::from_iter([
+                    ("Type", "Annot".into()),
+                    ("Subtype", "Link".into()),
+                    ("Rect", rect.map(|mm| Pt::from(mm).0.into()).to_vec().into()),
+                    ("Border", vec![0.into(), 0.into(), 0.into()].into()),
+                    (
+                        "Dest",
+                        vec![page_ids[link.target].into(), "Fit".into()].into(),
+                    ),
+                ]);
+                document.add_object(annotation).into()
+            })
+            .collect::<Vec<Object>>();
+        let page_dictionary = document.get_object_mut(page_id)?.as_dict_mut()?;
+        page_dictionary.set("Annots", annotations);
+    }
+
+    // printpdf leaves font files uncompressed, so compress every stream that isn't already.
+    for object in document.objects.values_mut() {
+        if let Object::Stream(stream) = object {
+            stream.compress()?;
+        }
+    }
+
+    let labels = (0..).zip(&scene.pages).flat_map(|(i, page): (i64, _)| {
+        [
+            i.into(),
+            Dictionary::from_iter([("P", text_string(&page.title))]).into(),
+        ]
+    });
+    let labels = Dictionary::from_iter([("Nums", labels.collect::<Vec<Object>>().into())]);
+
+    let catalog_id = document.trailer.get(b"Root")?.as_reference()?;
+    let catalog = document.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("Outlines", outline_id);
+    catalog.set("PageMode", "UseOutlines");
+    catalog.set("PageLabels", labels);
+
+    let info_id = document.trailer.get(b"Info")?.as_reference()?;
+    let info_dictionary = document.get_object_mut(info_id)?.as_dict_mut()?;
+    info_dictionary.remove(b"GTS_PDFXVersion");
+    info_dictionary.set("Title", text_string(info.title()));
+    let optional = [("Author", &info.author), ("Subject", &info.subject)];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            info_dictionary.set(key, text_string(value));
+        }
+    }
+    if !info.keywords.is_empty() {
+        info_dictionary.set("Keywords", text_string(&info.keywords.join(", ")));
+    }
+    Ok(())
+}
+
+/// Make `items` the children of `parent` in the outline, in order, and add them to the
+/// document.
+fn add_outline_items(
+    document: &mut lopdf::Document,
+    parent: &mut Dictionary,
+    items: Vec<(ObjectId, Dictionary)>,
+    open: bool,
+) {
+    let ids = items.iter().map(|&(id, _)| id).collect::<Vec<_>>();
+    let (Some(&first), Some(&last)) = (ids.first(), ids.last()) else {
+        return;
+    };
+    parent.set("First", first);
+    parent.set("Last", last);
+    let count = ids.len() as i64;
+    parent.set("Count", if open { count } else { -count });
+    for (i, (id, mut item)) in items.into_iter().enumerate() {
+        if let Some(&previous) = i.checked_sub(1).and_then(|i| ids.get(i)) {
+            item.set("Prev", previous);
+        }
+        if let Some(&next) = ids.get(i + 1) {
+            item.set("Next", next);
+        }
+        document.objects.insert(id, item.into());
+    }
+}
+
+/// A PDF text string, which has to be UTF-16 to hold anything beyond ASCII.
+fn text_string(s: &str) -> Object {
+    if s.is_ascii() {
+        return Object::string_literal(s);
+    }
+    let utf16 = s.encode_utf16().flat_map(u16::to_be_bytes);
+    let bytes = [0xFE, 0xFF].into_iter().chain(utf16).collect();
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+/// Add the glyphs of `font` that `scene` uses to the document, or the whole font if it can't be
+/// cut down.
+fn add_font(
+    document: &PdfDocumentReference,
+    font: &Font,
+    scene: &Scene,
+) -> Result<IndirectFontRef, Error> {
+    let glyphs = (scene.pages.iter())
+        .flat_map(|page| &page.items)
+        .filter_map(|item| match item {
+            Item::Text(text) if text.style == font.style => Some(text.content.chars()),
+            _ => None,
+        })
+        .flatten()
+        .map(|c| (c, font.face.glyph(c).id().0))
+        .filter(|&(_, id)| id != 0)
+        .collect();
+    let subset = subset::subset(&font.data, &glyphs);
+    document
+        .add_external_font(subset.as_deref().unwrap_or(&font.data))
+        .map_err(pdf_error)
+}
+
+fn pdf_error(error: impl StdError + Send + Sync + 'static) -> Error {
+    Error::Pdf(Box::new(error))
+}
+
+struct Surface<'document> {
+    document: &'document PdfDocumentReference,
+    /// Indexed by `FontStyle`.
+    fonts: [IndirectFontRef; 3],
+    page: Option<Page>,
+}
+
+struct Page {
+    layer: PdfLayerReference,
+    y: Mm,
+}
+
+impl Surface<'_> {
+    fn page(&self) -> &Page {
+        self.page.as_ref().expect("drew before adding a page")
+    }
+}
+
+impl draw::Renderer for Surface<'_> {
+    fn add_page(&mut self, _label: &str, (x, y): (Mm, Mm)) {
+        let (page, layer) = self.document.add_page(x, y, "");
+        let layer = self.document.get_page(page).get_layer(layer);
+        self.page = Some(Page { layer, y });
+    }
+
+    fn draw_text(&mut self, text: &TextRun) {
+        let (x, y) = text.position;
+        let page = self.page();
+        let font = &self.fonts[text.style as usize];
+        page.layer.begin_text_section();
+        page.layer.set_fill_color(color(text.colour));
+        page.layer.set_font(font, f64::from(text.size));
+        page.layer.set_text_cursor(x, page.y - y);
+        page.layer.write_text(&text.content, font);
+        page.layer.end_text_section();
+    }
+
+    fn draw_rect(&mut self, (left, top, width, height): (Mm, Mm, Mm, Mm), colour: Colour) {
+        let page = self.page();
+        page.layer.set_fill_color(color(colour));
+        page.layer.add_shape(Line {
+            points: vec![
+                (Point::new(left, page.y - top), false),
+                (Point::new(left + width, page.y - top), false),
+                (Point::new(left + width, page.y - (top + height)), false),
+                (Point::new(left, page.y - (top + height)), false),
+            ],
+            is_closed: true,
+            has_fill: true,
+            has_stroke: false,
+            is_clipping_path: false,
+        });
+    }
+
+    fn draw_circle(&mut self, (x, y): (Mm, Mm), radius: Mm, colour: Colour) {
+        const POINTS: u32 = 60;
+        let page = self.page();
+        page.layer.set_fill_color(color(colour));
+        page.layer.add_shape(Line {
+            points: (0..POINTS)
+                .map(|i| {
+                    let angle = f64::from(i) / f64::from(POINTS) * f64::consts::TAU;
+                    let x = x + radius * angle.cos();
+                    let y = y - radius * angle.sin();
+                    (Point::new(x, page.y - y), false)
+                })
+                .collect(),
+            is_closed: true,
+            has_fill: true,
+            has_stroke: false,
+            is_clipping_path: false,
+        });
+    }
+}
+
+fn color(Colour([r, g, b]): Colour) -> Color {
+    Color::Rgb(Rgb::new(
+        f64::from(r) / 255.0,
+        f64::from(g) / 255.0,
+        f64::from(b) / 255.0,
+        None,
+    ))
+}
+
+use crate::draw;
+use crate::draw::Font;
+use crate::draw::Fonts;
+use crate::draw::Item;
+use crate::draw::Scene;
+use crate::draw::TextRun;
+use crate::log;
+use crate::log::Colour;
+use crate::log::Log;
+use crate::subset;
+use crate::Error;
+use printpdf::lopdf;
+use printpdf::lopdf::Dictionary;
+use printpdf::lopdf::Object;
+use printpdf::lopdf::ObjectId;
+use printpdf::lopdf::StringFormat;
+use printpdf::Color;
+use printpdf::CustomPdfConformance;
+use printpdf::IndirectFontRef;
+use printpdf::Line;
+use printpdf::Mm;
+use printpdf::PdfConformance;
+use printpdf::PdfDocument;
+use printpdf::PdfDocumentReference;
+use printpdf::PdfLayerReference;
+use printpdf::Point;
+use printpdf::Pt;
+use printpdf::Rgb;
+use std::error::Error as StdError;
+use std::f64;
+use std::io::Write;
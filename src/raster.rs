@@ -0,0 +1,232 @@
+//! PNG output, drawn without any outside renderer.
+
+/// Options that only apply to PNG output.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Options {
+    /// The resolution to render at, in pixels per inch.
+    pub dpi: f64,
+    /// Stack every page into one image, rather than writing one image per page.
+    pub single_file: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            dpi: 150.0,
+            single_file: false,
+        }
+    }
+}
+
+impl Options {
+    /// Set [`Options::dpi`].
+    pub fn dpi(mut self, dpi: f64) -> Self {
+        self.dpi = dpi;
+        self
+    }
+    /// Set [`Options::single_file`].
+    pub fn single_file(mut self, single_file: bool) -> Self {
+        self.single_file = single_file;
+        self
+    }
+}
+
+/// Render the log to `{stem}.png` if all pages go into a single image, and to `{stem}-{label}.png`
+/// for each page's label otherwise.
+pub fn render(
+    log: &Log,
+    options: &draw::Options,
+    png_options: &Options,
+    stem: &str,
+) -> Result<(), Error> {
+    if png_options.single_file {
+        let path = format!("{stem}.png");
+        return crate::write_file(&path, |out| write(log, options, png_options, out));
+    }
+    for page in pages(log, options, png_options)? {
+        crate::write_file(&format!("{stem}-{}.png", page.label), |out| {
+            Ok(encode(&page.image, out)?)
+        })?;
+    }
+    Ok(())
+}
+
+/// Write every page of the log to `out` as a single PNG, stacked from top to bottom.
+pub fn write(
+    log: &Log,
+    options: &draw::Options,
+    png_options: &Options,
+    out: impl io::Write,
+) -> Result<(), Error> {
+    let pages = pages(log, options, png_options)?;
+    let gap = (draw::PAGE_GAP.0 * png_options.dpi / 25.4).round() as u32;
+    let images = || pages.iter().map(|page| &page.image);
+    let width = images().map(|image| image.width).max().unwrap_or(0);
+    let height = images().map(|image| image.height + gap).sum::<u32>();
+    // The gaps between pages are left transparent.
+    let mut combined = Image::new(width, height.saturating_sub(gap), [0; 4]);
+    let mut y = 0;
+    for image in images() {
+        combined.paste(image, y);
+        y += image.height + gap;
+    }
+    Ok(encode(&combined, out)?)
+}
+
+/// The days of the log chosen by `options`, drawn page by page.
+fn pages(log: &Log, options: &draw::Options, png_options: &Options) -> Result<Vec<Page>, Error> {
+    let log = &crate::chosen_days(log, options)?;
+    let fonts = Fonts::load(&options.style(log))?;
+    let mut surface = Surface {
+        fonts: &fonts,
+        pixels_per_mm: png_options.dpi / 25.4,
+        pages: Vec::new(),
+    };
+    draw::calendar(log, options, &fonts).render(&mut surface);
+    Ok(surface.pages)
+}
+
+fn encode(image: &Image, out: impl io::Write) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(out, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let data = image.pixels.iter().flatten().copied().collect::<Vec<u8>>();
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+    Ok(())
+}
+
+struct Surface<'fonts> {
+    fonts: &'fonts Fonts,
+    pixels_per_mm: f64,
+    pages: Vec<Page>,
+}
+
+struct Page {
+    label: String,
+    image: Image,
+}
+
+/// An 8-bit RGBA image.
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    fn new(width: u32, height: u32, fill: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width as usize * height as usize],
+        }
+    }
+
+    /// Blend `colour` into the pixel at `(x, y)`, where `coverage` is the proportion of the pixel
+    /// that the shape covers. Pixels outside the image are ignored.
+    fn blend(&mut self, (x, y): (i64, i64), colour: Colour, coverage: f64) {
+        let outside = x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height);
+        if outside || coverage <= 0.0 {
+            return;
+        }
+        let coverage = coverage.min(1.0);
+        let pixel = &mut self.pixels[y as usize * self.width as usize + x as usize];
+        for (channel, new) in pixel.iter_mut().zip(colour.0) {
+            let blended = f64::from(*channel) * (1.0 - coverage) + f64::from(new) * coverage;
+            *channel = blended.round() as u8;
+        }
+        pixel[3] = 255;
+    }
+
+    /// Copy `image` into this one, with its top edge at row `y`.
+    fn paste(&mut self, image: &Image, y: u32) {
+        for (row, pixels) in image.pixels.chunks(image.width as usize).enumerate() {
+            let start = (y as usize + row) * self.width as usize;
+            self.pixels[start..start + pixels.len()].copy_from_slice(pixels);
+        }
+    }
+}
+
+impl Surface<'_> {
+    fn image(&mut self) -> &mut Image {
+        let page = self.pages.last_mut().expect("drew before adding a page");
+        &mut page.image
+    }
+
+    fn px(&self, mm: Mm) -> f64 {
+        mm.0 * self.pixels_per_mm
+    }
+}
+
+impl draw::Renderer for Surface<'_> {
+    fn add_page(&mut self, label: &str, (width, height): (Mm, Mm)) {
+        let width = self.px(width).round() as u32;
+        let height = self.px(height).round() as u32;
+        self.pages.push(Page {
+            label: label.to_owned(),
+            image: Image::new(width, height, [255; 4]),
+        });
+    }
+
+    fn draw_text(&mut self, text: &TextRun) {
+        let (x, y) = text.position;
+        let font = self.fonts.get(text.style);
+        // Text is measured in points, of which there are 72 to the inch.
+        let pixels_per_pt = (self.pixels_per_mm * 25.4 / 72.0) as f32;
+        let scale = font.scale(text.size);
+        let scale = rusttype::Scale {
+            x: scale.x * pixels_per_pt,
+            y: scale.y * pixels_per_pt,
+        };
+        let start = rusttype::point(self.px(x) as f32, self.px(y) as f32);
+        let image = self.image();
+        for glyph in font.face.layout(&text.content, scale, start) {
+            let Some(bounds) = glyph.pixel_bounding_box() else {
+                continue;
+            };
+            glyph.draw(|x, y, coverage| {
+                let x = i64::from(bounds.min.x) + i64::from(x);
+                let y = i64::from(bounds.min.y) + i64::from(y);
+                image.blend((x, y), text.colour, f64::from(coverage));
+            });
+        }
+    }
+
+    fn draw_rect(&mut self, (left, top, width, height): (Mm, Mm, Mm, Mm), colour: Colour) {
+        let (x0, y0) = (self.px(left), self.px(top));
+        let (x1, y1) = (self.px(left + width), self.px(top + height));
+        let image = self.image();
+        for y in y0.floor() as i64..y1.ceil() as i64 {
+            // Partially covered pixels at the edges are blended by how much of them is covered.
+            let y_coverage = (y1.min(y as f64 + 1.0) - y0.max(y as f64)).max(0.0);
+            for x in x0.floor() as i64..x1.ceil() as i64 {
+                let x_coverage = (x1.min(x as f64 + 1.0) - x0.max(x as f64)).max(0.0);
+                image.blend((x, y), colour, x_coverage * y_coverage);
+            }
+        }
+    }
+
+    fn draw_circle(&mut self, (x, y): (Mm, Mm), radius: Mm, colour: Colour) {
+        let (cx, cy, r) = (self.px(x), self.px(y), self.px(radius));
+        let image = self.image();
+        for y in (cy - r).floor() as i64..(cy + r).ceil() as i64 {
+            for x in (cx - r).floor() as i64..(cx + r).ceil() as i64 {
+                // Approximate the coverage of the pixel by how far inside the circle its centre is.
+                let distance = (x as f64 + 0.5 - cx).hypot(y as f64 + 0.5 - cy);
+                image.blend((x, y), colour, r - distance + 0.5);
+            }
+        }
+    }
+}
+
+use crate::draw;
+use crate::draw::Fonts;
+use crate::draw::TextRun;
+use crate::log::Colour;
+use crate::log::Log;
+use crate::Error;
+use printpdf::Mm;
+use std::io;
@@ -0,0 +1,181 @@
+/// The days of a log, stored as runs of consecutive days with the same highlight so that logs
+/// covering decades stay small.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Runs {
+    /// The index of the first day of each run, increasing from 0.
+    starts: Vec<u32>,
+    /// The day that each run repeats. Neighbouring runs are never the same.
+    days: Vec<Day>,
+    len: u32,
+}
+
+impl Runs {
+    /// Add a day to the end.
+    pub(crate) fn push(&mut self, day: Day) {
+        if self.days.last() != Some(&day) {
+            self.starts.push(self.len);
+            self.days.push(day);
+        }
+        self.len = self.len.checked_add(1).expect("too many days");
+    }
+
+    /// The number of days.
+    pub(crate) fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// The day at index `i`, in logarithmic time.
+    pub(crate) fn get(&self, i: usize) -> Option<Day> {
+        (i < self.len()).then(|| self.days[self.run_containing(i)])
+    }
+
+    /// The index of the run that day `i` is part of, or 0 if there are no runs.
+    fn run_containing(&self, i: usize) -> usize {
+        (self.starts.partition_point(|&start| start as usize <= i)).saturating_sub(1)
+    }
+
+    /// Every run in order, as the indices of the days it covers and the day it repeats.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Range<usize>, Day)> + '_ {
+        self.iter_in(0..self.len())
+    }
+
+    /// The runs covering the days in `range` in order, cut down to fit it, starting from the run
+    /// containing its first day.
+    pub(crate) fn iter_in(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (Range<usize>, Day)> + '_ {
+        let first = if range.is_empty() {
+            self.days.len()
+        } else {
+            self.run_containing(range.start)
+        };
+        let ends = (self.starts[(first + 1).min(self.starts.len())..].iter())
+            .map(|&start| start as usize)
+            .chain([self.len()]);
+        (self.starts[first..]
+            .iter()
+            .zip(ends)
+            .zip(&self.days[first..]))
+        .map(|((&start, end), &day)| (start as usize..end, day))
+        .take_while(move |(run, _)| run.start < range.end)
+        .map(move |(run, day)| (run.start.max(range.start)..run.end.min(range.end), day))
+    }
+
+    /// Every day in order.
+    pub(crate) fn days(&self) -> Days<'_> {
+        self.days_from(0)
+    }
+
+    /// Every day in order from index `start`, which must be at most the length.
+    pub(crate) fn days_from(&self, start: usize) -> Days<'_> {
+        assert!(start <= self.len(), "start out of bounds");
+        Days {
+            runs: self,
+            next: start,
+            run: self.run_containing(start),
+        }
+    }
+
+    /// A copy of the days in `range`, which must be in bounds.
+    pub(crate) fn slice(&self, range: Range<usize>) -> Self {
+        assert!(range.end <= self.len(), "range out of bounds");
+        let mut runs = Self::default();
+        for (run, day) in self.iter_in(range) {
+            runs.starts.push(runs.len);
+            runs.days.push(day);
+            runs.len += run.len() as u32;
+        }
+        runs
+    }
+}
+
+impl FromIterator<Day> for Runs {
+    fn from_iter<I: IntoIterator<Item = Day>>(iter: I) -> Self {
+        let mut runs = Self::default();
+        for day in iter {
+            runs.push(day);
+        }
+        runs
+    }
+}
+
+/// An iterator over every day of some [`Runs`].
+#[derive(Debug, Clone)]
+pub(crate) struct Days<'runs> {
+    runs: &'runs Runs,
+    /// The index of the next day.
+    next: usize,
+    /// The index of the run that the next day is in.
+    run: usize,
+}
+
+impl Iterator for Days<'_> {
+    type Item = Day;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.runs.len() {
+            return None;
+        }
+        if (self.runs.starts.get(self.run + 1)).is_some_and(|&start| start as usize == self.next) {
+            self.run += 1;
+        }
+        self.next += 1;
+        Some(self.runs.days[self.run])
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+impl ExactSizeIterator for Days<'_> {
+    fn len(&self) -> usize {
+        self.runs.len() - self.next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn runs() {
+        let highlights = [None, Some(0), Some(0), Some(1), None, None, None, Some(0)];
+        let mut runs = Runs::default();
+        for highlight in highlights {
+            runs.push(Day::new(highlight));
+        }
+        assert_eq!(runs.len(), 8);
+        assert_eq!(runs.starts, [0, 1, 3, 4, 7]);
+
+        for (i, highlight) in highlights.into_iter().enumerate() {
+            assert_eq!(runs.get(i), Some(Day::new(highlight)));
+        }
+        assert_eq!(runs.get(8), None);
+
+        let days = runs.days().map(Day::highlight).collect::<Vec<_>>();
+        assert_eq!(days, highlights);
+        let days = runs.days_from(3).map(Day::highlight).collect::<Vec<_>>();
+        assert_eq!(days, highlights[3..]);
+        assert_eq!(runs.days_from(8).len(), 0);
+        let ranges = runs.iter().map(|(range, _)| range).collect::<Vec<_>>();
+        assert_eq!(ranges, [0..1, 1..3, 3..4, 4..7, 7..8]);
+        let ranges = runs
+            .iter_in(2..5)
+            .map(|(range, _)| range)
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, [2..3, 3..4, 4..5]);
+        assert_eq!(runs.iter_in(5..5).count(), 0);
+        assert_eq!(runs.iter_in(7..8).count(), 1);
+
+        let slice = runs.slice(2..6);
+        assert_eq!(slice.len(), 4);
+        let days = slice.days().map(Day::highlight).collect::<Vec<_>>();
+        assert_eq!(days, highlights[2..6]);
+        assert_eq!(slice.starts, [0, 1, 2]);
+        assert_eq!(runs.slice(3..3), Runs::default());
+    }
+
+    use super::Runs;
+    use crate::log::Day;
+}
+
+use crate::log::Day;
+use std::ops::Range;
@@ -0,0 +1,528 @@
+/// Counts of each highlight by year, month and weekday, and their streaks. Shown as text with
+/// `Display`.
+pub struct Stats<'log> {
+    highlights: Vec<&'log str>,
+    from: Date,
+    to: Date,
+    total: Bucket,
+    years: BTreeMap<i32, Bucket>,
+    months: BTreeMap<(i32, u8), Bucket>,
+    weekdays: [Bucket; 7],
+    /// Indexed by highlight.
+    streaks: Vec<streaks::Summary<'log>>,
+}
+
+impl<'log> Stats<'log> {
+    /// Count the highlighted days of the log between `from` and `to` inclusive, defaulting to the
+    /// whole log.
+    pub fn new(log: &'log Log, from: Option<Date>, to: Option<Date>) -> Result<Self, Error> {
+        let highlights = log
+            .highlights()
+            .iter()
+            .map(Highlight::name)
+            .collect::<Vec<_>>();
+        let indices = highlights
+            .iter()
+            .enumerate()
+            .map(|(i, &name)| (name, i))
+            .collect::<ahash::HashMap<_, _>>();
+
+        let empty = Bucket::new(highlights.len());
+        let mut stats = Self {
+            from: Date::MAX,
+            to: Date::MIN,
+            total: empty.clone(),
+            years: BTreeMap::new(),
+            months: BTreeMap::new(),
+            weekdays: array::from_fn(|_| empty.clone()),
+            streaks: Vec::new(),
+            highlights,
+        };
+
+        let dates = (
+            from.map_or(Bound::Unbounded, Bound::Included),
+            to.map_or(Bound::Unbounded, Bound::Included),
+        );
+        for run in log.runs_in(dates) {
+            stats.from = stats.from.min(run.start());
+            stats.to = stats.to.max(run.end());
+
+            let highlight = run.highlight().map(|highlight| indices[highlight.name()]);
+            let days = run.days();
+            stats.total.add(highlight, days);
+
+            // Split the run where months end, so that each part is within one year and month.
+            let mut start = run.start();
+            loop {
+                let (year, month) = (start.year(), start.month());
+                let last_day = time::util::days_in_year_month(year, month);
+                let month_end = Date::from_calendar_date(year, month, last_day).unwrap();
+                let end = month_end.min(run.end());
+                let days = u32::try_from((end - start).whole_days()).unwrap() + 1;
+                let buckets = [
+                    stats.years.entry(year).or_insert_with(|| empty.clone()),
+                    (stats.months.entry((year, u8::from(month)))).or_insert_with(|| empty.clone()),
+                ];
+                for bucket in buckets {
+                    bucket.add(highlight, days);
+                }
+                match end.next_day() {
+                    Some(next) if end < run.end() => start = next,
+                    _ => break,
+                }
+            }
+
+            // Every whole week adds a day to each weekday, and the rest of the run adds one to the
+            // weekdays it starts on.
+            let first = run.start().weekday().number_days_from_monday();
+            for i in 0..7 {
+                let weekday = usize::from((first + i) % 7);
+                let days = days / 7 + u32::from(u32::from(i) < days % 7);
+                stats.weekdays[weekday].add(highlight, days);
+            }
+        }
+
+        if stats.total.days == 0 {
+            return Err(Error::NoDays);
+        }
+
+        let found = streaks::find(log, dates);
+        stats.streaks = (log.highlights().iter())
+            .map(|highlight| streaks::Summary::new(&found, highlight, stats.to))
+            .collect();
+
+        Ok(stats)
+    }
+
+    /// Write the statistics as a JSON object.
+    pub fn write_json<W: Write>(&self, writer: W) -> io::Result<()> {
+        let json = Json {
+            from: self.from,
+            to: self.to,
+            total: self.total.json(&self.highlights),
+            years: (self.years.iter())
+                .map(|(&year, bucket)| JsonYear {
+                    year,
+                    bucket: bucket.json(&self.highlights),
+                })
+                .collect(),
+            months: (self.months.iter())
+                .map(|(&(year, month), bucket)| JsonMonth {
+                    year,
+                    month,
+                    bucket: bucket.json(&self.highlights),
+                })
+                .collect(),
+            weekdays: (WEEKDAYS.into_iter().zip(&self.weekdays))
+                .map(|(weekday, bucket)| JsonWeekday {
+                    weekday: weekday.to_string(),
+                    bucket: bucket.json(&self.highlights),
+                })
+                .collect(),
+            streaks: JsonStreaks {
+                names: &self.highlights,
+                summaries: &self.streaks,
+            },
+        };
+        Ok(serde_json::to_writer_pretty(writer, &json)?)
+    }
+
+    fn table<'a>(
+        &self,
+        f: &mut Formatter<'_>,
+        title: &str,
+        rows: impl IntoIterator<Item = (String, &'a Bucket)>,
+    ) -> fmt::Result {
+        writeln!(f, "{title}")?;
+        write!(f, "{:10} {:>6}", "", "days")?;
+        for name in &self.highlights {
+            write!(f, "  {name:>width$}", width = column_width(name))?;
+        }
+        writeln!(f)?;
+
+        for (label, bucket) in rows {
+            write!(f, "{label:10} {:>6}", bucket.days)?;
+            for (name, &count) in self.highlights.iter().zip(&bucket.counts) {
+                let percentage = percentage(count, bucket.days);
+                let cell = format!("{count} {percentage:5.1}%");
+                write!(f, "  {cell:>width$}", width = column_width(name))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Stats<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} to {}", self.from, self.to)?;
+        writeln!(f)?;
+
+        let years = self.years.iter();
+        self.table(f, "By year", years.map(|(year, b)| (year.to_string(), b)))?;
+        writeln!(f)?;
+
+        let months = self.months.iter();
+        let months = months.map(|(&(year, month), b)| (format!("{year}-{month:02}"), b));
+        self.table(f, "By month", months)?;
+        writeln!(f)?;
+
+        let weekdays = WEEKDAYS.into_iter().zip(&self.weekdays);
+        self.table(f, "By weekday", weekdays.map(|(w, b)| (w.to_string(), b)))?;
+        writeln!(f)?;
+
+        self.table(f, "Total", [(String::new(), &self.total)])?;
+        writeln!(f)?;
+
+        writeln!(f, "Streaks    current  longest  from        to")?;
+        for (name, summary) in self.highlights.iter().zip(&self.streaks) {
+            write!(f, "{name:10} {:>7}", summary.current)?;
+            match summary.longest {
+                Some(longest) => writeln!(
+                    f,
+                    "  {:>7}  {}  {}",
+                    longest.days(),
+                    longest.start,
+                    longest.end
+                )?,
+                None => writeln!(f, "  {:>7}", 0)?,
+            }
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Streak lengths (days: count)")?;
+        for (name, summary) in self.highlights.iter().zip(&self.streaks) {
+            let mut line = format!("{name:10}");
+            for (days, count) in &summary.histogram {
+                write!(line, " {days}: {count}")?;
+            }
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+fn column_width(name: &str) -> usize {
+    // wide enough for `9999 100.0%`
+    name.chars().count().max(11)
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+#[derive(Clone)]
+struct Bucket {
+    days: u32,
+    /// Indexed by highlight.
+    counts: Vec<u32>,
+}
+
+impl Bucket {
+    fn new(highlights: usize) -> Self {
+        Self {
+            days: 0,
+            counts: vec![0; highlights],
+        }
+    }
+
+    /// Count `days` more days with `highlight`.
+    fn add(&mut self, highlight: Option<usize>, days: u32) {
+        self.days += days;
+        if let Some(highlight) = highlight {
+            self.counts[highlight] += days;
+        }
+    }
+
+    fn json<'a>(&'a self, highlights: &'a [&'a str]) -> JsonBucket<'a> {
+        let counts = JsonCounts {
+            names: highlights,
+            counts: &self.counts,
+            days: self.days,
+        };
+        JsonBucket {
+            days: self.days,
+            highlights: counts,
+            percentages: JsonPercentages(counts),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Json<'a> {
+    from: Date,
+    to: Date,
+    #[serde(flatten)]
+    total: JsonBucket<'a>,
+    years: Vec<JsonYear<'a>>,
+    months: Vec<JsonMonth<'a>>,
+    weekdays: Vec<JsonWeekday<'a>>,
+    streaks: JsonStreaks<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonYear<'a> {
+    year: i32,
+    #[serde(flatten)]
+    bucket: JsonBucket<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonMonth<'a> {
+    year: i32,
+    month: u8,
+    #[serde(flatten)]
+    bucket: JsonBucket<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonWeekday<'a> {
+    weekday: String,
+    #[serde(flatten)]
+    bucket: JsonBucket<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonBucket<'a> {
+    days: u32,
+    highlights: JsonCounts<'a>,
+    percentages: JsonPercentages<'a>,
+}
+
+/// Serialized as a map from highlight name to count, in the order the highlights were declared.
+#[derive(Clone, Copy)]
+struct JsonCounts<'a> {
+    names: &'a [&'a str],
+    counts: &'a [u32],
+    /// The number of days the counts are out of.
+    days: u32,
+}
+
+impl Serialize for JsonCounts<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.names.iter().zip(self.counts))
+    }
+}
+
+/// Serialized like `JsonCounts`, but with each count as a percentage of the days.
+struct JsonPercentages<'a>(JsonCounts<'a>);
+
+impl Serialize for JsonPercentages<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Self(counts) = self;
+        let percentages = (counts.counts.iter()).map(|&count| percentage(count, counts.days));
+        serializer.collect_map(counts.names.iter().zip(percentages))
+    }
+}
+
+/// `count` as a percentage of `days`, or 0 if there are no days, such as for a weekday that a short
+/// range doesn't include.
+fn percentage(count: u32, days: u32) -> f64 {
+    match days {
+        0 => 0.0,
+        days => f64::from(count) / f64::from(days) * 100.0,
+    }
+}
+
+/// Serialized as a map from highlight name to a summary of its streaks.
+struct JsonStreaks<'a> {
+    names: &'a [&'a str],
+    summaries: &'a [streaks::Summary<'a>],
+}
+
+impl Serialize for JsonStreaks<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let summaries = self.summaries.iter().map(|summary| JsonStreakSummary {
+            current: summary.current,
+            longest: summary.longest.map(|longest| JsonStreak {
+                days: longest.days(),
+                from: longest.start,
+                to: longest.end,
+            }),
+            histogram: &summary.histogram,
+        });
+        serializer.collect_map(self.names.iter().zip(summaries))
+    }
+}
+
+#[derive(Serialize)]
+struct JsonStreakSummary<'a> {
+    current: u32,
+    longest: Option<JsonStreak>,
+    histogram: &'a BTreeMap<u32, u32>,
+}
+
+#[derive(Serialize)]
+struct JsonStreak {
+    days: u32,
+    from: Date,
+    to: Date,
+}
+
+#[cfg(test)]
+mod tests {
+    fn log() -> Log {
+        "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            b = { shape = \"rectangle\", colour = \"#0000FF\" }\n\
+            [data]\n\
+            2022-12-30.Fri = \"a\"\n\
+            2022-12-31.Sat = \"a\"\n\
+            2023-01-01.Sun = \"\"\n\
+            2023-01-02.Mon = \"b\"\n\
+            2023-01-03.Tue = \"a\"\n"
+            .parse()
+            .unwrap()
+    }
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn counts(bucket: &Bucket) -> (u32, &[u32]) {
+        (bucket.days, &bucket.counts)
+    }
+
+    #[test]
+    fn buckets() {
+        let log = log();
+        let stats = Stats::new(&log, None, None).unwrap();
+        assert_eq!(stats.highlights, ["a", "b"]);
+        assert_eq!(stats.from, date(2022, Month::December, 30));
+        assert_eq!(stats.to, date(2023, Month::January, 3));
+        assert_eq!(counts(&stats.total), (5, &[3, 1][..]));
+
+        let years = (stats.years.iter())
+            .map(|(&year, bucket)| (year, counts(bucket)))
+            .collect::<Vec<_>>();
+        assert_eq!(years, [(2022, (2, &[2, 0][..])), (2023, (3, &[1, 1][..]))]);
+        let months = (stats.months.iter())
+            .map(|(&month, bucket)| (month, counts(bucket)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            months,
+            [
+                ((2022, 12), (2, &[2, 0][..])),
+                ((2023, 1), (3, &[1, 1][..]))
+            ],
+        );
+        let weekdays = stats.weekdays.each_ref().map(counts);
+        assert_eq!(
+            weekdays,
+            [
+                (1, &[0, 1][..]),
+                (1, &[1, 0][..]),
+                (0, &[0, 0][..]),
+                (0, &[0, 0][..]),
+                (1, &[1, 0][..]),
+                (1, &[1, 0][..]),
+                (1, &[0, 0][..]),
+            ],
+        );
+
+        let [a, b] = &stats.streaks[..] else {
+            panic!("expected a summary for each highlight");
+        };
+        assert_eq!(a.current, 1);
+        assert_eq!(a.longest.unwrap().days(), 2);
+        assert_eq!(b.current, 0);
+    }
+
+    #[test]
+    fn range() {
+        let log = log();
+        let from = date(2022, Month::December, 31);
+        let to = date(2023, Month::January, 2);
+        let stats = Stats::new(&log, Some(from), Some(to)).unwrap();
+        assert_eq!((stats.from, stats.to), (from, to));
+        assert_eq!(counts(&stats.total), (3, &[1, 1][..]));
+        assert_eq!(counts(&stats.years[&2022]), (1, &[1, 0][..]));
+        assert_eq!(counts(&stats.weekdays[5]), (1, &[1, 0][..]));
+        assert_eq!(counts(&stats.weekdays[4]), (0, &[0, 0][..]));
+        // Weekdays outside the range are empty, and have no percentages to speak of.
+        let text = stats.to_string();
+        let friday = text
+            .lines()
+            .find(|line| line.starts_with("Friday"))
+            .unwrap();
+        assert!(friday.ends_with("0   0.0%     0   0.0%"), "{friday}");
+        let mut out = Vec::new();
+        stats.write_json(&mut out).unwrap();
+        let json = serde_json::from_slice::<serde_json::Value>(&out).unwrap();
+        let friday = &json["weekdays"][4];
+        assert_eq!(friday["weekday"], "Friday");
+        assert_eq!(
+            friday["percentages"],
+            serde_json::json!({ "a": 0.0, "b": 0.0 })
+        );
+        // Streaks are cut short at the edges of the range.
+        assert_eq!(stats.streaks[0].longest.unwrap().days(), 1);
+        assert_eq!(stats.streaks[1].current, 1);
+
+        let outside = date(2024, Month::January, 1);
+        assert!(Stats::new(&log, Some(outside), None).is_err());
+        assert!(Stats::new(&log, Some(to), Some(from)).is_err());
+    }
+
+    #[test]
+    fn json() {
+        let log = log();
+        let mut out = Vec::new();
+        let stats = Stats::new(&log, None, None).unwrap();
+        stats.write_json(&mut out).unwrap();
+        let json = serde_json::from_slice::<serde_json::Value>(&out).unwrap();
+        assert_eq!(json["from"], "2022-12-30");
+        assert_eq!(json["to"], "2023-01-03");
+        assert_eq!(json["days"], 5);
+        assert_eq!(json["highlights"], serde_json::json!({ "a": 3, "b": 1 }));
+        assert_eq!(
+            json["percentages"],
+            serde_json::json!({ "a": 60.0, "b": 20.0 })
+        );
+        assert_eq!(json["years"][1]["year"], 2023);
+        assert_eq!(json["years"][1]["highlights"]["b"], 1);
+        assert_eq!(json["months"][0]["month"], 12);
+        assert_eq!(json["months"][0]["percentages"]["a"], 100.0);
+        assert_eq!(json["weekdays"][0]["weekday"], "Monday");
+        assert_eq!(json["weekdays"][0]["highlights"]["b"], 1);
+        assert_eq!(json["streaks"]["a"]["current"], 1);
+        assert_eq!(
+            json["streaks"]["a"]["longest"],
+            serde_json::json!({ "days": 2, "from": "2022-12-30", "to": "2022-12-31" }),
+        );
+        assert_eq!(
+            json["streaks"]["b"]["histogram"],
+            serde_json::json!({ "1": 1 })
+        );
+    }
+
+    use super::Bucket;
+    use super::Stats;
+    use crate::log::Log;
+    use time::Date;
+    use time::Month;
+}
+
+use crate::log::Highlight;
+use crate::log::Log;
+use crate::streaks;
+use crate::Error;
+use serde::Serialize;
+use serde::Serializer;
+use std::array;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write;
+use std::ops::Bound;
+use time::Date;
+use time::Weekday;
@@ -0,0 +1,115 @@
+/// A maximal run of consecutive days that all have the same highlight.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Streak<'log> {
+    pub highlight: &'log Highlight,
+    pub start: Date,
+    pub end: Date,
+}
+
+impl Streak<'_> {
+    pub(crate) fn days(&self) -> u32 {
+        u32::try_from((self.end - self.start).whole_days()).unwrap() + 1
+    }
+}
+
+/// Find every streak in the log within `dates`, in order. Streaks that cross the edges of the range
+/// are cut short.
+pub(crate) fn find(log: &Log, dates: impl RangeBounds<Date>) -> Vec<Streak<'_>> {
+    // Neighbouring runs never share a highlight, so every highlighted run is a whole streak.
+    (log.runs_in(dates))
+        .filter_map(|run| {
+            Some(Streak {
+                highlight: run.highlight()?,
+                start: run.start(),
+                end: run.end(),
+            })
+        })
+        .collect()
+}
+
+/// The streaks of a single highlight.
+#[derive(Debug)]
+pub(crate) struct Summary<'log> {
+    /// The length of the streak that runs up to the final day, or zero if there is none.
+    pub current: u32,
+    /// The earliest of the longest streaks.
+    pub longest: Option<Streak<'log>>,
+    /// How many streaks there are of each length.
+    pub histogram: BTreeMap<u32, u32>,
+}
+
+impl<'log> Summary<'log> {
+    /// Summarise the streaks of `highlight`, where `last_day` is the final day considered.
+    pub(crate) fn new(streaks: &[Streak<'log>], highlight: &Highlight, last_day: Date) -> Self {
+        let mut summary = Self {
+            current: 0,
+            longest: None,
+            histogram: BTreeMap::new(),
+        };
+        for streak in streaks {
+            if !ptr::eq(streak.highlight, highlight) {
+                continue;
+            }
+            if streak.end == last_day {
+                summary.current = streak.days();
+            }
+            if summary.longest.map(|l| l.days()) < Some(streak.days()) {
+                summary.longest = Some(*streak);
+            }
+            *summary.histogram.entry(streak.days()).or_default() += 1;
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn find_streaks() {
+        let log = "[highlights]\n\
+            a = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            b = { shape = \"circle\", colour = \"#0000FF\" }\n\
+            [data]\n\
+            2023-01-01.Sun = \"a\"\n\
+            2023-01-02.Mon = \"a\"\n\
+            2023-01-03.Tue = \"b\"\n\
+            2023-01-04.Wed = \"\"\n\
+            2023-01-05.Thu = \"a\"\n\
+            2023-01-06.Fri = \"a\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let streaks = find(&log, ..)
+            .into_iter()
+            .map(|s| (s.highlight.name(), s.start.day(), s.days()))
+            .collect::<Vec<_>>();
+        assert_eq!(streaks, [("a", 1, 2), ("b", 3, 1), ("a", 5, 2)]);
+
+        let streaks = find(&log, jan(2)..);
+        assert_eq!(streaks[0].start, jan(2));
+        assert_eq!(streaks[0].days(), 1);
+
+        let a = &log.highlights()[0];
+        let summary = Summary::new(&streaks, a, jan(6));
+        assert_eq!(summary.current, 2);
+        assert_eq!(summary.longest.unwrap().start, jan(5));
+        assert_eq!(summary.histogram, BTreeMap::from([(1, 1), (2, 1)]));
+    }
+
+    fn jan(day: u8) -> Date {
+        Date::from_calendar_date(2023, Month::January, day).unwrap()
+    }
+
+    use super::find;
+    use super::Summary;
+    use crate::log::Log;
+    use std::collections::BTreeMap;
+    use time::Date;
+    use time::Month;
+}
+
+use crate::log::Highlight;
+use crate::log::Log;
+use std::collections::BTreeMap;
+use std::ops::RangeBounds;
+use std::ptr;
+use time::Date;
@@ -0,0 +1,349 @@
+macro_rules! style {
+    ($($(#[doc = $doc:literal])* $field:ident: $ty:ty,)*) => {
+        /// How the calendar looks. Lengths are in millimetres and text sizes in points.
+        #[derive(Debug, Clone, PartialEq, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        #[non_exhaustive]
+        pub struct Style {
+            $($(#[doc = $doc])* pub $field: $ty,)*
+        }
+
+        /// A partial `Style`, as written in a theme file or the `[style]` table of the log.
+        #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+        #[serde(deny_unknown_fields)]
+        pub struct Overrides {
+            $(#[serde(skip_serializing_if = "Option::is_none")] $field: Option<$ty>,)*
+        }
+
+        impl Style {
+            /// This style with every field set in `overrides` replaced.
+            pub fn with(&self, overrides: &Overrides) -> Self {
+                Self {
+                    $($field: overrides.$field.clone().unwrap_or_else(|| self.$field.clone()),)*
+                }
+            }
+        }
+    };
+}
+
+style! {
+    /// The month each page starts with, such as 9 for academic years running from September.
+    first_month: MonthNumber,
+    /// The width of every page.
+    page_width: f64,
+    /// The height of every page.
+    page_height: f64,
+    /// The colour of the whole page.
+    background: Colour,
+    /// The colour of the title, day numbers and captions.
+    text_colour: Colour,
+
+    /// The path of the font for day numbers and the notes on month pages.
+    regular_font: String,
+    /// The path of the font for titles and month names.
+    bold_font: String,
+    /// The path of the font for weekday initials, week numbers and longest streaks.
+    italic_font: String,
+
+    /// The size of each page's title.
+    title_size: f32,
+    /// The space above and below the title.
+    title_padding: f64,
+    /// The space at the left and right edges of the page.
+    margin: f64,
+    /// The space between columns of months.
+    column_gap: f64,
+
+    /// The background of each month's header.
+    header_colour: Colour,
+    /// The colour of the month names in the headers.
+    header_text_colour: Colour,
+    /// The size of the month names in the headers.
+    header_size: f32,
+    /// The space around the month name in its header.
+    header_padding: f64,
+
+    /// The colour of the weekday initials above each month.
+    weekday_colour: Colour,
+    /// The size of the weekday initials above each month.
+    weekday_size: f32,
+    /// The day each week starts on, which comes first in every row.
+    week_start: DayOfWeek,
+
+    /// Which week numbers to show in a column to the left of each month, if any.
+    week_numbers: WeekNumbers,
+    /// The colour of the week numbers.
+    week_number_colour: Colour,
+    /// The size of the week numbers.
+    week_number_size: f32,
+
+    /// The days of the week to shade, such as `["Fri", "Sat"]`.
+    non_working_days: Weekdays,
+    /// The shading of non-working days.
+    non_working_colour: Colour,
+    /// The shading of public holidays, beneath any highlights.
+    holiday_colour: Colour,
+    /// The colour of public holidays' day numbers.
+    holiday_text_colour: Colour,
+
+    /// The outline around today, or the `--as-of` day.
+    today_colour: Colour,
+    /// The width of the outline around today.
+    today_line_width: f64,
+    /// The colour of day numbers after today, or after the `--as-of` day.
+    future_text_colour: Colour,
+
+    /// The size of the day numbers.
+    day_size: f32,
+    /// The space above and below each day number.
+    day_spacing: f64,
+    /// How far highlight circles reach beyond the day number.
+    highlight_padding: f64,
+
+    /// The size of the longest streaks and of the notes on month pages.
+    caption_size: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        toml::from_str(CLASSIC).expect("the classic theme is invalid")
+    }
+}
+
+impl Style {
+    /// Look up a built-in theme by name, or else read a theme file from the path `theme`.
+    pub fn theme(theme: &str) -> Result<Self, Error> {
+        let overrides = match THEMES.iter().find(|&&(name, _)| name == theme) {
+            Some((_, source)) => toml::from_str(source).expect("a built-in theme is invalid"),
+            None => {
+                let source = fs::read_to_string(theme).map_err(|source| Error::UnknownTheme {
+                    theme: theme.to_owned(),
+                    source,
+                })?;
+                toml::from_str::<Overrides>(&source).map_err(|e| Error::Theme {
+                    theme: theme.to_owned(),
+                    source: ParseError::new(&e, &source),
+                })?
+            }
+        };
+        Ok(Self::default().with(&overrides))
+    }
+
+    /// The file that [`Style::theme`] reads `theme` from, or `None` if it is a built-in theme.
+    pub fn theme_file(theme: &str) -> Option<&str> {
+        (!THEMES.iter().any(|&(name, _)| name == theme)).then_some(theme)
+    }
+
+    /// Every font file the style uses.
+    pub fn font_paths(&self) -> [&str; 3] {
+        [&self.regular_font, &self.bold_font, &self.italic_font]
+    }
+}
+
+/// A month, written as its number from 1 to 12.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonthNumber(pub Month);
+
+impl Serialize for MonthNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(u8::from(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for MonthNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let number = u8::deserialize(deserializer)?;
+        Month::try_from(number).map(Self).map_err(|_| {
+            let unexpected = de::Unexpected::Unsigned(u64::from(number));
+            de::Error::invalid_value(unexpected, &"a month number from 1 to 12")
+        })
+    }
+}
+
+/// A day of the week, written as its abbreviation like `"Mon"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayOfWeek(pub Weekday);
+
+impl DayOfWeek {
+    /// The days of a week that starts on this day, in order.
+    pub fn week(self) -> [Weekday; 7] {
+        let mut day = self.0;
+        [(); 7].map(|()| {
+            let today = day;
+            day = day.next();
+            today
+        })
+    }
+
+    /// How far into a week that starts on this day `weekday` comes, from 0 to 6.
+    pub fn position(self, weekday: Weekday) -> u8 {
+        (7 + weekday.number_days_from_monday() - self.0.number_days_from_monday()) % 7
+    }
+}
+
+impl Serialize for DayOfWeek {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let i = self.0.number_days_from_monday();
+        serializer.serialize_str(WEEKDAY_NAMES[usize::from(i)])
+    }
+}
+
+impl<'de> Deserialize<'de> for DayOfWeek {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        let i = (WEEKDAY_NAMES.iter().position(|&n| n == name))
+            .ok_or_else(|| de::Error::unknown_variant(&name, &WEEKDAY_NAMES))?;
+        Ok(Self(Weekday::Sunday.nth_next(i as u8 + 1)))
+    }
+}
+
+/// A set of days of the week, written as a list of their abbreviations like `"Mon"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weekdays([bool; 7]);
+
+impl Weekdays {
+    /// Whether `weekday` is in the set.
+    pub fn contains(self, weekday: Weekday) -> bool {
+        self.0[usize::from(weekday.number_days_from_monday())]
+    }
+}
+
+impl Serialize for Weekdays {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let names =
+            (WEEKDAY_NAMES.iter().zip(self.0)).filter_map(|(name, day)| day.then_some(name));
+        serializer.collect_seq(names)
+    }
+}
+
+impl<'de> Deserialize<'de> for Weekdays {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut weekdays = [false; 7];
+        for name in Vec::<String>::deserialize(deserializer)? {
+            let i = (WEEKDAY_NAMES.iter().position(|&n| n == name))
+                .ok_or_else(|| de::Error::unknown_variant(&name, &WEEKDAY_NAMES))?;
+            weekdays[i] = true;
+        }
+        Ok(Self(weekdays))
+    }
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// A way of numbering the weeks of a year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum WeekNumbers {
+    /// No week numbers.
+    None,
+    /// ISO 8601: weeks start on Monday, and week 1 is the one with the year's first Thursday.
+    Iso,
+    /// Weeks start on Sunday, and week 1 is the one with January 1st.
+    Us,
+    /// Weeks start on Monday, and week 1 is the one with January 1st.
+    Monday,
+}
+
+impl WeekNumbers {
+    /// The number of the week that the seven days from `row_start` fall in. When the row lines up
+    /// with the numbering's weeks, a row containing January 1st is week 1; otherwise this picks the
+    /// week of the middle day.
+    pub fn number(self, row_start: Date) -> Option<u8> {
+        let middle = row_start + Duration::days(3);
+        let week_start = match self {
+            Self::None => return None,
+            Self::Iso => return Some(middle.iso_week()),
+            Self::Us => DayOfWeek(Weekday::Sunday),
+            Self::Monday => DayOfWeek(Weekday::Monday),
+        };
+        // Every day of a lined-up row is in the same week, and the last one is in the new year if
+        // any of them are.
+        let date = if row_start.weekday() == week_start.0 {
+            row_start + Duration::days(6)
+        } else {
+            middle
+        };
+        let january_1 = date.replace_ordinal(1).unwrap();
+        let days = date.ordinal() - 1 + u16::from(week_start.position(january_1.weekday()));
+        Some((days / 7 + 1) as u8)
+    }
+}
+
+const CLASSIC: &str = include_str!("../themes/classic.toml");
+
+pub(crate) const THEMES: [(&str, &str); 4] = [
+    ("classic", CLASSIC),
+    ("minimal", include_str!("../themes/minimal.toml")),
+    ("dark", include_str!("../themes/dark.toml")),
+    (
+        "high-contrast",
+        include_str!("../themes/high-contrast.toml"),
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn themes_parse() {
+        let classic = Style::default();
+        assert_eq!(classic.day_size, 10.0);
+        for (name, _) in THEMES {
+            Style::theme(name).unwrap();
+        }
+        let dark = Style::theme("dark").unwrap();
+        assert_eq!(dark.background.0, [0x1E; 3]);
+        assert_eq!(dark.day_size, classic.day_size);
+    }
+
+    #[test]
+    fn week_numbers() {
+        let date = |year, month, day| Date::from_calendar_date(year, month, day).unwrap();
+        // 2021 starts on a Friday, so Monday 4 January starts ISO week 1.
+        let monday = date(2021, Month::January, 4);
+        assert_eq!(WeekNumbers::Iso.number(monday), Some(1));
+        assert_eq!(WeekNumbers::Monday.number(monday), Some(2));
+        assert_eq!(WeekNumbers::Us.number(monday), Some(2));
+        // A row from Sunday 3 January is mostly ISO week 1, even though it starts in week 53.
+        let sunday = date(2021, Month::January, 3);
+        assert_eq!(WeekNumbers::Iso.number(sunday), Some(1));
+        assert_eq!(WeekNumbers::Us.number(sunday), Some(2));
+        // The row from Sunday 27 December contains 1 January, so it is US week 1.
+        let sunday = date(2020, Month::December, 27);
+        assert_eq!(WeekNumbers::Iso.number(sunday), Some(53));
+        assert_eq!(WeekNumbers::Us.number(sunday), Some(1));
+        assert_eq!(
+            WeekNumbers::Us.number(date(2025, Month::December, 28)),
+            Some(1)
+        );
+        assert_eq!(WeekNumbers::None.number(sunday), None);
+
+        // January 2022 under Monday numbering: 1 January is a Saturday in the first row.
+        let rows = [27, 3, 10, 17, 24, 31].map(|day| match day {
+            27 => date(2021, Month::December, day),
+            _ => date(2022, Month::January, day),
+        });
+        let numbers = rows.map(|row| WeekNumbers::Monday.number(row).unwrap());
+        assert_eq!(numbers, [1, 2, 3, 4, 5, 6]);
+    }
+
+    use super::Style;
+    use super::WeekNumbers;
+    use super::THEMES;
+    use time::Date;
+    use time::Month;
+}
+
+use crate::log::Colour;
+use crate::log::ParseError;
+use crate::Error;
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::fs;
+use time::Date;
+use time::Duration;
+use time::Month;
+use time::Weekday;
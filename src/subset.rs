@@ -0,0 +1,297 @@
+/// Cut a TrueType font down to the outlines of `glyphs`, which maps every character to be drawn to
+/// its glyph ID, so that documents only carry the glyphs they use. Glyph IDs stay the same and the
+/// character map only covers `glyphs`. Returns `None` for fonts that aren't TrueType outlines, such
+/// as collections and CFF fonts, which have to be used whole.
+pub(crate) fn subset(font: &[u8], glyphs: &BTreeMap<char, u16>) -> Option<Vec<u8>> {
+    let tables = tables(font)?;
+    let head = *tables.get(b"head")?;
+    let glyf = *tables.get(b"glyf")?;
+    let loca = *tables.get(b"loca")?;
+    let glyph_count = usize::from(u16_at(tables.get(b"maxp")?, 4)?);
+    let long_offsets = u16_at(head, 50)? == 1;
+    let outline = |id: usize| {
+        let (start, end) = if long_offsets {
+            (
+                u32_at(loca, 4 * id)? as usize,
+                u32_at(loca, 4 * id + 4)? as usize,
+            )
+        } else {
+            let at = |i| Some(usize::from(u16_at(loca, 2 * i)?) * 2);
+            (at(id)?, at(id + 1)?)
+        };
+        glyf.get(start..end)
+    };
+
+    // Glyph 0 is drawn for anything missing, and composite glyphs are made of other glyphs.
+    let mut kept = BTreeSet::from([0]);
+    let mut pending = glyphs
+        .values()
+        .map(|&id| usize::from(id))
+        .collect::<Vec<_>>();
+    while let Some(id) = pending.pop() {
+        if id < glyph_count && kept.insert(id) {
+            pending.extend(components(outline(id)?)?);
+        }
+    }
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity(4 * (glyph_count + 1));
+    for id in 0..glyph_count {
+        new_loca.extend(u32::try_from(new_glyf.len()).ok()?.to_be_bytes());
+        if kept.contains(&id) {
+            new_glyf.extend(outline(id)?);
+            new_glyf.resize(new_glyf.len().next_multiple_of(4), 0);
+        }
+    }
+    new_loca.extend(u32::try_from(new_glyf.len()).ok()?.to_be_bytes());
+
+    // Offsets are always written long, and the checksum adjustment is filled in at the end.
+    let mut new_head = head.to_vec();
+    new_head.get_mut(8..12)?.fill(0);
+    new_head
+        .get_mut(50..52)?
+        .copy_from_slice(&1u16.to_be_bytes());
+
+    let mut subset = BTreeMap::from([
+        (*b"cmap", character_map(glyphs)),
+        (*b"glyf", new_glyf),
+        (*b"head", new_head),
+        (*b"loca", new_loca),
+    ]);
+    // Glyph names are the bulk of `post`, and nothing needs them, so keep only the header.
+    if let Some(post) = tables.get(b"post").and_then(|post| post.get(..32)) {
+        let mut post = post.to_vec();
+        post[..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+        subset.insert(*b"post", post);
+    }
+    // Everything else is either needed to draw the glyphs or small, except the layout tables,
+    // which PDF text doesn't use.
+    for tag in [
+        b"OS/2", b"cvt ", b"fpgm", b"gasp", b"hhea", b"hmtx", b"maxp", b"name", b"prep",
+    ] {
+        if let Some(&table) = tables.get(tag) {
+            subset.insert(*tag, table.to_vec());
+        }
+    }
+    let mut font = write(&subset);
+    let head_offset = 12 + 16 * subset.keys().position(|tag| tag == b"head")?;
+    let offset = u32_at(&font, head_offset + 8)? as usize;
+    let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+    font[offset + 8..offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    Some(font)
+}
+
+/// The family name of a TrueType font, which its bold and italic faces share, such as "DejaVu Sans".
+pub(crate) fn family_name(font: &[u8]) -> Option<String> {
+    const TYPOGRAPHIC_FAMILY: u16 = 16;
+    const FAMILY: u16 = 1;
+
+    let name = *tables(font)?.get(b"name")?;
+    let strings = usize::from(u16_at(name, 4)?);
+    let mut found = None;
+    for i in 0..usize::from(u16_at(name, 2)?) {
+        let record = 6 + 12 * i;
+        let [platform, _, _, id, length, offset] =
+            [0, 2, 4, 6, 8, 10].map(|field| u16_at(name, record + field));
+        let (platform, id) = (platform?, id?);
+        let start = strings + usize::from(offset?);
+        let bytes = name.get(start..start + usize::from(length?))?;
+        // Unicode and Windows names are UTF-16; Macintosh ones are usually ASCII.
+        let string = match platform {
+            0 | 3 => {
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .ok()
+            }
+            1 if bytes.is_ascii() => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+            _ => None,
+        };
+        match (id, string) {
+            (TYPOGRAPHIC_FAMILY, Some(string)) => return Some(string),
+            (FAMILY, Some(string)) => found = found.or(Some(string)),
+            _ => {}
+        }
+    }
+    found
+}
+
+/// The tables of a TrueType font by tag, or `None` for other fonts.
+fn tables(font: &[u8]) -> Option<BTreeMap<[u8; 4], &[u8]>> {
+    let version = u32_at(font, 0)?;
+    if version != 0x0001_0000 && version != u32::from_be_bytes(*b"true") {
+        return None;
+    }
+    let mut tables = BTreeMap::new();
+    for i in 0..usize::from(u16_at(font, 4)?) {
+        let record = 12 + 16 * i;
+        let tag: [u8; 4] = font.get(record..record + 4)?.try_into().ok()?;
+        let offset = u32_at(font, record + 8)? as usize;
+        let length = u32_at(font, record + 12)? as usize;
+        tables.insert(tag, font.get(offset..offset.checked_add(length)?)?);
+    }
+    Some(tables)
+}
+
+/// The glyphs that a glyph is made of, if it is a composite.
+fn components(outline: &[u8]) -> Option<Vec<usize>> {
+    const ARGS_ARE_WORDS: u16 = 0x0001;
+    const HAVE_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const HAVE_X_AND_Y_SCALE: u16 = 0x0040;
+    const HAVE_TWO_BY_TWO: u16 = 0x0080;
+
+    let mut components = Vec::new();
+    // Empty glyphs and simple glyphs, which have a number of contours rather than -1.
+    if outline.is_empty() || u16_at(outline, 0)? & 0x8000 == 0 {
+        return Some(components);
+    }
+    let mut at = 10;
+    loop {
+        let flags = u16_at(outline, at)?;
+        components.push(usize::from(u16_at(outline, at + 2)?));
+        at += 4 + if flags & ARGS_ARE_WORDS != 0 { 4 } else { 2 };
+        at += if flags & HAVE_SCALE != 0 {
+            2
+        } else if flags & HAVE_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & HAVE_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            return Some(components);
+        }
+    }
+}
+
+/// A `cmap` table with a single format 12 subtable for Unicode, grouping runs of consecutive
+/// characters with consecutive glyphs.
+fn character_map(glyphs: &BTreeMap<char, u16>) -> Vec<u8> {
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for (&c, &id) in glyphs {
+        let (c, id) = (u32::from(c), u32::from(id));
+        match groups.last_mut() {
+            Some((start, end, start_id)) if c == *end + 1 && id == *start_id + (c - *start) => {
+                *end = c;
+            }
+            _ => groups.push((c, c, id)),
+        }
+    }
+    let length = 16 + 12 * groups.len() as u32;
+    let mut cmap = Vec::new();
+    for field in [0u16, 1, 3, 10] {
+        cmap.extend(field.to_be_bytes());
+    }
+    cmap.extend(12u32.to_be_bytes());
+    cmap.extend([0, 12, 0, 0]);
+    for field in [length, 0, groups.len() as u32] {
+        cmap.extend(field.to_be_bytes());
+    }
+    for (start, end, start_id) in groups {
+        for field in [start, end, start_id] {
+            cmap.extend(field.to_be_bytes());
+        }
+    }
+    cmap
+}
+
+/// A whole font file made of `tables`, with its table directory.
+fn write(tables: &BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let entry_selector = count.ilog2() as u16;
+    let search_range = 16 << entry_selector;
+    let mut font = Vec::new();
+    for field in [
+        1,
+        0,
+        count,
+        search_range,
+        entry_selector,
+        count * 16 - search_range,
+    ] {
+        font.extend(field.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, table) in tables {
+        font.extend(tag);
+        font.extend(checksum(table).to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for table in tables.values() {
+        font.extend(table);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    font
+}
+
+/// The sum of `data` as big-endian 32-bit words, padded with zeros.
+fn checksum(data: &[u8]) -> u32 {
+    (data.chunks(4))
+        .map(|word| {
+            let mut padded = [0; 4];
+            padded[..word.len()].copy_from_slice(word);
+            u32::from_be_bytes(padded)
+        })
+        .fold(0, u32::wrapping_add)
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn subset() {
+        let fonts = Fonts::load(&Style::default()).unwrap();
+        let font = &fonts.regular.face;
+        let glyphs = "2023 Janéé"
+            .chars()
+            .map(|c| (c, font.glyph(c).id().0))
+            .collect::<BTreeMap<_, _>>();
+        let data = super::subset(&fonts.regular.data, &glyphs).unwrap();
+        assert!(data.len() * 4 < fonts.regular.data.len());
+
+        let subset = rusttype::Font::try_from_vec(data).unwrap();
+        assert_eq!(subset.glyph_count(), font.glyph_count());
+        let scale = rusttype::Scale::uniform(100.0);
+        let bounds = |font: &rusttype::Font<'_>, c: char| {
+            let glyph = font.glyph(c);
+            (glyph.id(), glyph.scaled(scale).exact_bounding_box())
+        };
+        // Accented letters are usually composites of other glyphs, which have to come along.
+        for c in glyphs.keys().copied() {
+            assert_eq!(bounds(&subset, c), bounds(font, c));
+        }
+        // Other characters are no longer mapped, and their outlines are gone.
+        assert_eq!(subset.glyph('K').id().0, 0);
+        let k = font.glyph('K').id();
+        assert!(subset.glyph(k).scaled(scale).exact_bounding_box().is_none());
+    }
+
+    #[test]
+    fn family_name() {
+        let fonts = Fonts::load(&Style::default()).unwrap();
+        for font in [&fonts.regular, &fonts.bold, &fonts.italic] {
+            assert_eq!(super::family_name(&font.data).unwrap(), "DejaVu Sans");
+        }
+        assert!(super::family_name(b"not a font").is_none());
+    }
+
+    use crate::draw::Fonts;
+    use crate::style::Style;
+    use std::collections::BTreeMap;
+}
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
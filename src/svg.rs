@@ -0,0 +1,301 @@
+//! SVG output.
+
+/// Options that only apply to SVG output.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Options {
+    /// Draw text as paths traced from the glyph outlines, instead of as text elements.
+    pub outlines: bool,
+    /// Stack every page into one file, rather than writing one file per page.
+    pub single_file: bool,
+}
+
+impl Options {
+    /// Set [`Options::outlines`].
+    pub fn outlines(mut self, outlines: bool) -> Self {
+        self.outlines = outlines;
+        self
+    }
+    /// Set [`Options::single_file`].
+    pub fn single_file(mut self, single_file: bool) -> Self {
+        self.single_file = single_file;
+        self
+    }
+}
+
+/// Render the log to `{stem}.svg` if all pages go into a single file, and to `{stem}-{label}.svg`
+/// for each page's label otherwise.
+pub fn render(
+    log: &Log,
+    options: &draw::Options,
+    svg_options: &Options,
+    stem: &str,
+) -> Result<(), Error> {
+    if svg_options.single_file {
+        let path = format!("{stem}.svg");
+        return crate::write_file(&path, |out| write(log, options, svg_options, out));
+    }
+    for page in pages(log, options, svg_options)? {
+        let mut svg = String::new();
+        write_header(&mut svg, page.size);
+        write_page(&mut svg, &page, 0.0);
+        svg.push_str("</svg>\n");
+        crate::write_file(&format!("{stem}-{}.svg", page.label), |out| {
+            out.extend_from_slice(svg.as_bytes());
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Write every page of the log to `out` as a single SVG, stacked from top to bottom.
+pub fn write(
+    log: &Log,
+    options: &draw::Options,
+    svg_options: &Options,
+    mut out: impl io::Write,
+) -> Result<(), Error> {
+    let pages = pages(log, options, svg_options)?;
+    let width = (pages.iter())
+        .map(
+            |&Page {
+                 size: (Mm(width), _),
+                 ..
+             }| width,
+        )
+        .fold(0.0, f64::max);
+    let height = (pages.iter())
+        .map(
+            |&Page {
+                 size: (_, Mm(height)),
+                 ..
+             }| height + draw::PAGE_GAP.0,
+        )
+        .sum::<f64>()
+        - draw::PAGE_GAP.0;
+
+    let mut svg = String::new();
+    write_header(&mut svg, (Mm(width), Mm(height.max(0.0))));
+    let mut y = 0.0;
+    for page in &pages {
+        write_page(&mut svg, page, y);
+        y += (page.size.1 + draw::PAGE_GAP).0;
+    }
+    svg.push_str("</svg>\n");
+    out.write_all(svg.as_bytes())?;
+    Ok(())
+}
+
+/// The days of the log chosen by `options`, drawn page by page.
+fn pages(log: &Log, options: &draw::Options, svg_options: &Options) -> Result<Vec<Page>, Error> {
+    let log = &crate::chosen_days(log, options)?;
+    let fonts = Fonts::load(&options.style(log))?;
+    let mut surface = Surface {
+        fonts: &fonts,
+        outlines: svg_options.outlines,
+        pages: Vec::new(),
+    };
+    draw::calendar(log, options, &fonts).render(&mut surface);
+    Ok(surface.pages)
+}
+
+fn write_header(svg: &mut String, (Mm(width), Mm(height)): (Mm, Mm)) {
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+        width=\"{width}mm\" height=\"{height}mm\" viewBox=\"0 0 {width} {height}\">"
+    )
+    .unwrap();
+}
+
+fn write_page(svg: &mut String, page: &Page, y: f64) {
+    let (Mm(width), Mm(height)) = page.size;
+    writeln!(svg, "<g transform=\"translate(0 {y:.3})\">").unwrap();
+    writeln!(
+        svg,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"#FFFFFF\"/>"
+    )
+    .unwrap();
+    svg.push_str(&page.body);
+    svg.push_str("</g>\n");
+}
+
+struct Surface<'fonts> {
+    fonts: &'fonts Fonts,
+    outlines: bool,
+    pages: Vec<Page>,
+}
+
+struct Page {
+    label: String,
+    size: (Mm, Mm),
+    body: String,
+}
+
+impl Surface<'_> {
+    fn body(&mut self) -> &mut String {
+        &mut self
+            .pages
+            .last_mut()
+            .expect("drew before adding a page")
+            .body
+    }
+}
+
+impl draw::Renderer for Surface<'_> {
+    fn add_page(&mut self, label: &str, size: (Mm, Mm)) {
+        self.pages.push(Page {
+            label: label.to_owned(),
+            size,
+            body: String::new(),
+        });
+    }
+
+    fn draw_text(&mut self, text: &TextRun) {
+        let (x, y) = text.position;
+        let fill = hex(text.colour);
+        if self.outlines {
+            let mut path = Path(String::new());
+            let font = self.fonts.get(text.style);
+            let scale = font.scale(text.size);
+            let start = rusttype::point(0.0, 0.0);
+            for glyph in font.face.layout(&text.content, scale, start) {
+                let offset = glyph.position();
+                glyph
+                    .unpositioned()
+                    .build_outline(&mut Offset(&mut path, offset));
+            }
+            // Glyphs are laid out in points, so scale them down to millimetres.
+            let scale = to_mm(1.0).0;
+            writeln!(
+                self.body(),
+                "<path transform=\"translate({:.3} {:.3}) scale({scale:.5})\" d=\"{}\" fill=\"{fill}\"/>",
+                x.0,
+                y.0,
+                path.0.trim_end(),
+            )
+            .unwrap();
+        } else {
+            let style = match text.style {
+                FontStyle::Regular => "",
+                FontStyle::Bold => " font-weight=\"bold\"",
+                FontStyle::Italic => " font-style=\"oblique\"",
+            };
+            // Viewers pick the face from the family and the weight or style.
+            let family = match &self.fonts.get(text.style).family {
+                Some(family) => format!("'{}', sans-serif", family.replace(['\'', '\\'], "")),
+                None => "sans-serif".to_owned(),
+            };
+            writeln!(
+                self.body(),
+                "<text x=\"{:.3}\" y=\"{:.3}\" font-family=\"{}\" font-size=\"{:.3}\"{style} \
+                fill=\"{fill}\">{}</text>",
+                x.0,
+                y.0,
+                Escape(&family),
+                to_mm(text.size).0,
+                Escape(&text.content),
+            )
+            .unwrap();
+        }
+    }
+
+    fn draw_rect(&mut self, (left, top, width, height): (Mm, Mm, Mm, Mm), colour: Colour) {
+        writeln!(
+            self.body(),
+            "<rect x=\"{:.3}\" y=\"{:.3}\" width=\"{:.3}\" height=\"{:.3}\" fill=\"{}\"/>",
+            left.0,
+            top.0,
+            width.0,
+            height.0,
+            hex(colour),
+        )
+        .unwrap();
+    }
+
+    fn draw_circle(&mut self, (x, y): (Mm, Mm), radius: Mm, colour: Colour) {
+        writeln!(
+            self.body(),
+            "<circle cx=\"{:.3}\" cy=\"{:.3}\" r=\"{:.3}\" fill=\"{}\"/>",
+            x.0,
+            y.0,
+            radius.0,
+            hex(colour),
+        )
+        .unwrap();
+    }
+}
+
+pub(crate) fn hex(Colour([r, g, b]): Colour) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
+/// SVG path data, built from glyph outlines.
+struct Path(String);
+
+/// Moves every point of an outline by a fixed amount.
+struct Offset<'path>(&'path mut Path, rusttype::Point<f32>);
+
+impl rusttype::OutlineBuilder for Offset<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let Self(Path(d), o) = self;
+        write!(d, "M{:.2} {:.2} ", x + o.x, y + o.y).unwrap();
+    }
+    fn line_to(&mut self, x: f32, y: f32) {
+        let Self(Path(d), o) = self;
+        write!(d, "L{:.2} {:.2} ", x + o.x, y + o.y).unwrap();
+    }
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let Self(Path(d), o) = self;
+        write!(
+            d,
+            "Q{:.2} {:.2} {:.2} {:.2} ",
+            x1 + o.x,
+            y1 + o.y,
+            x + o.x,
+            y + o.y
+        )
+        .unwrap();
+    }
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let Self(Path(d), o) = self;
+        let (x1, y1, x2, y2, x, y) = (x1 + o.x, y1 + o.y, x2 + o.x, y2 + o.y, x + o.x, y + o.y);
+        write!(d, "C{x1:.2} {y1:.2} {x2:.2} {y2:.2} {x:.2} {y:.2} ").unwrap();
+    }
+    fn close(&mut self) {
+        self.0 .0.push_str("Z ");
+    }
+}
+
+/// Escapes text for use in XML.
+pub(crate) struct Escape<'s>(pub &'s str);
+
+impl Display for Escape<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+use crate::draw;
+use crate::draw::to_mm;
+use crate::draw::FontStyle;
+use crate::draw::Fonts;
+use crate::draw::TextRun;
+use crate::log::Colour;
+use crate::log::Log;
+use crate::Error;
+use printpdf::Mm;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Write as _;
+use std::io;
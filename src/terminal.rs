@@ -0,0 +1,604 @@
+//! Month grids for the terminal and plain text output.
+
+/// How highlighted days are shown in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Colours {
+    /// Exact colours, using 24-bit escape codes.
+    TrueColour,
+    /// The closest of the 16 standard terminal colours.
+    Ansi16,
+    /// No escape codes at all; highlighted days are marked with a symbol instead.
+    None,
+}
+
+impl Colours {
+    /// Choose colours based on the environment, respecting `NO_COLOR` (<https://no-color.org>).
+    pub fn detect() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return Self::None;
+        }
+        match env::var("COLORTERM").as_deref() {
+            Ok("truecolor" | "24bit") => Self::TrueColour,
+            _ => Self::Ansi16,
+        }
+    }
+}
+
+/// What to show in the terminal.
+#[non_exhaustive]
+pub struct Options {
+    /// The year to show, or the year of `month`.
+    pub year: Option<i32>,
+    /// The month to show.
+    pub month: Option<Month>,
+    /// How to show highlights.
+    pub colours: Colours,
+    /// Mark this day and dim the ones after it.
+    pub as_of: Option<Date>,
+    /// The style to use where the log's `[style]` table doesn't say otherwise.
+    pub theme: Style,
+}
+
+impl Options {
+    /// The month of the last logged day, in `colours`, with the default theme.
+    pub fn new(colours: Colours) -> Self {
+        Self {
+            year: None,
+            month: None,
+            colours,
+            as_of: None,
+            theme: Style::default(),
+        }
+    }
+    /// Set [`Options::year`].
+    pub fn year(mut self, year: i32) -> Self {
+        self.year = Some(year);
+        self
+    }
+    /// Set [`Options::month`].
+    pub fn month(mut self, month: Month) -> Self {
+        self.month = Some(month);
+        self
+    }
+    /// Set [`Options::as_of`].
+    pub fn as_of(mut self, as_of: Date) -> Self {
+        self.as_of = Some(as_of);
+        self
+    }
+    /// Set [`Options::theme`].
+    pub fn theme(mut self, theme: Style) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+/// Print `cal`-style month grids followed by a legend. Without a year or month, the month of the
+/// last logged day is shown; with only a year, the whole year is.
+pub fn show(log: &Log, options: &Options, out: &mut impl Write) -> io::Result<()> {
+    let week_start = options.theme.with(log.style()).week_start;
+    let terminal = Terminal::new(log, options.colours, week_start, options.as_of);
+    let end_date = log.end_date();
+
+    let year = options.year.unwrap_or(end_date.year());
+    let months = match (options.year, options.month) {
+        (Some(_), None) => MONTHS.to_vec(),
+        (_, Some(month)) => vec![month],
+        (None, None) => vec![end_date.month()],
+    };
+    let months = months.into_iter().map(|month| (year, month));
+    terminal.months(&months.collect::<Vec<_>>(), out)?;
+
+    writeln!(out)?;
+    writeln!(out, "{}", terminal.legend())
+}
+
+/// Write every page year of the log as uncoloured month grids followed by a legend.
+pub fn write_plain(
+    log: &Log,
+    style: &Style,
+    as_of: Option<Date>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let terminal = Terminal::new(log, Colours::None, style.week_start, as_of);
+    for (i, page_year) in PageYear::all(log, style.first_month.0)
+        .into_iter()
+        .enumerate()
+    {
+        if i != 0 {
+            writeln!(out)?;
+        }
+        terminal.months(&page_year.months().collect::<Vec<_>>(), out)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "{}", terminal.legend())
+}
+
+struct Terminal<'log> {
+    log: &'log Log,
+    colours: Colours,
+    week_start: DayOfWeek,
+    as_of: Option<Date>,
+    /// Indexed by highlight.
+    symbols: Vec<String>,
+    /// The columns each day takes up, including the space or symbol after it.
+    cell_width: usize,
+}
+
+impl<'log> Terminal<'log> {
+    fn new(log: &'log Log, colours: Colours, week_start: DayOfWeek, as_of: Option<Date>) -> Self {
+        let symbols = symbols(log.highlights());
+        // Without colours, days are followed by their symbol, so every day makes room for the
+        // widest one.
+        let marker_width = match colours {
+            Colours::None => symbols.iter().map(|s| display_width(s)).max().unwrap_or(1),
+            _ => 1,
+        };
+        Self {
+            log,
+            colours,
+            week_start,
+            as_of,
+            symbols,
+            cell_width: 2 + marker_width.max(1),
+        }
+    }
+
+    /// Write the grids of `months`, three to a row.
+    fn months(&self, months: &[(i32, Month)], out: &mut impl Write) -> io::Result<()> {
+        for (i, row) in months.chunks(3).enumerate() {
+            if i != 0 {
+                writeln!(out)?;
+            }
+            let grids = (row.iter())
+                .map(|&(year, month)| self.month(year, month))
+                .collect::<Vec<_>>();
+            let mut lines = (0..grids[0].len())
+                .map(|line| {
+                    let line = grids.iter().map(|grid| &*grid[line]).collect::<Vec<_>>();
+                    line.join(" ").trim_end().to_owned()
+                })
+                .collect::<Vec<_>>();
+            while lines.last().is_some_and(String::is_empty) {
+                lines.pop();
+            }
+            for line in lines {
+                writeln!(out, "{line}")?;
+            }
+        }
+
+        let holidays = months.iter().flat_map(|&(year, month)| {
+            (1..=time::util::days_in_year_month(year, month)).filter_map(move |day| {
+                let date = Date::from_calendar_date(year, month, day).unwrap();
+                Some((date, self.log.holiday(date)?))
+            })
+        });
+        let mut holidays = holidays.peekable();
+        if holidays.peek().is_some() {
+            writeln!(out)?;
+            for (date, name) in holidays {
+                let month = &date.month().to_string()[..3];
+                writeln!(out, "{:2} {month} {}  {name}", date.day(), date.year())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The lines of one month's grid, each exactly seven days wide once escape codes are ignored.
+    fn month(&self, year: i32, month: Month) -> Vec<String> {
+        let (cell, width) = (self.cell_width, 7 * self.cell_width);
+        let title = format!("{month} {year}");
+        let weekdays = self
+            .week_start
+            .week()
+            .map(|weekday| format!("{:cell$}", &weekday.to_string()[..2]));
+        let mut lines = vec![
+            format!("{title:^width$} ", width = width - 1),
+            weekdays.concat(),
+        ];
+
+        let first = Date::from_calendar_date(year, month, 1).unwrap();
+        let mut column = usize::from(self.week_start.position(first.weekday()));
+        let mut line = " ".repeat(cell * column);
+        let days = time::util::days_in_year_month(year, month);
+        let last = Date::from_calendar_date(year, month, days).unwrap();
+        let mut logged = self.log.range(first..=last).peekable();
+        for day in 1..=days {
+            let date = Date::from_calendar_date(year, month, day).unwrap();
+            let highlight = (logged.next_if(|&(logged, _)| logged == date))
+                .and_then(|(_, highlight)| highlight);
+            line.push_str(&self.day(date, highlight));
+            column += 1;
+            if column == 7 {
+                lines.push(mem::take(&mut line));
+                column = 0;
+            }
+        }
+        if column != 0 {
+            line.push_str(&" ".repeat(cell * (7 - column)));
+            lines.push(line);
+        }
+        // Every month takes up six weeks, so that months side by side stay aligned.
+        lines.resize(8, " ".repeat(width));
+        lines
+    }
+
+    /// A single day, `cell_width` columns wide. When escape codes are available, public holidays are
+    /// underlined, the `as_of` day is shown in reverse video and the days after it are dimmed;
+    /// otherwise the `as_of` day is followed by `<` and the days after it by `·`, unless they're
+    /// highlighted.
+    fn day(&self, date: Date, highlight: Option<&Highlight>) -> String {
+        let mut day = format!("{:2}", date.day());
+        if self.colours != Colours::None {
+            if self.log.holiday(date).is_some() {
+                day = format!("{UNDERLINE}{day}{NO_UNDERLINE}");
+            }
+            match self.as_of {
+                Some(as_of) if date == as_of => day = format!("{REVERSE}{day}{NO_REVERSE}"),
+                Some(as_of) if date > as_of => day = format!("{DIM}{day}{NO_DIM}"),
+                _ => {}
+            }
+        }
+        let Some(highlight) = highlight else {
+            let marker = match self.as_of {
+                _ if self.colours != Colours::None => " ",
+                Some(as_of) if date == as_of => "<",
+                Some(as_of) if date > as_of => "·",
+                _ => " ",
+            };
+            return format!("{day}{}", self.pad(marker));
+        };
+        match (self.colours, highlight.shape()) {
+            (Colours::None, _) => format!("{day}{}", self.pad(self.symbol(highlight))),
+            // Rectangles fill the gap to the next day, so that runs of them join up.
+            (_, Shape::Rectangle) => format!("{}{day} {RESET}", self.paint(highlight.colour())),
+            (_, Shape::Circle) => format!("{}{day}{RESET} ", self.paint(highlight.colour())),
+        }
+    }
+
+    fn legend(&self) -> String {
+        let entries = self
+            .log
+            .highlights()
+            .iter()
+            .map(|highlight| match self.colours {
+                Colours::None => format!("{} {}", self.symbol(highlight), highlight.name()),
+                _ => format!(
+                    "{}  {RESET} {}",
+                    self.paint(highlight.colour()),
+                    highlight.name()
+                ),
+            });
+        let mut entries = entries.collect::<Vec<_>>();
+        if let Some(as_of) = self.as_of {
+            entries.push(match self.colours {
+                Colours::None => format!("< as of {as_of}  · later"),
+                _ => format!("{REVERSE}  {NO_REVERSE} as of {as_of}"),
+            });
+        }
+        entries.join("  ")
+    }
+
+    /// `marker` followed by enough spaces to fill the rest of a day.
+    fn pad(&self, marker: &str) -> String {
+        let padding = self.cell_width - 2 - display_width(marker);
+        format!("{marker}{}", " ".repeat(padding))
+    }
+
+    fn symbol(&self, highlight: &Highlight) -> &str {
+        let highlights = self.log.highlights();
+        let i = (0..highlights.len())
+            .find(|&i| ptr::eq(&highlights[i], highlight))
+            .unwrap();
+        &self.symbols[i]
+    }
+
+    /// The escape codes that set the background to `colour`, with text in a readable colour on
+    /// top.
+    fn paint(&self, Colour(rgb): Colour) -> String {
+        match self.colours {
+            Colours::TrueColour => {
+                let [r, g, b] = rgb;
+                let [fr, fg, fb] = if is_light(rgb) { [0; 3] } else { [255; 3] };
+                format!("\x1b[48;2;{r};{g};{b}m\x1b[38;2;{fr};{fg};{fb}m")
+            }
+            Colours::Ansi16 => {
+                let index = (0..ANSI_16.len())
+                    .min_by_key(|&i| distance(ANSI_16[i], rgb))
+                    .unwrap();
+                let background = if index < 8 {
+                    40 + index
+                } else {
+                    100 + index - 8
+                };
+                let foreground = if is_light(ANSI_16[index]) { 30 } else { 97 };
+                format!("\x1b[{background}m\x1b[{foreground}m")
+            }
+            Colours::None => String::new(),
+        }
+    }
+}
+
+pub(crate) const MONTHS: [Month; 12] = [
+    Month::January,
+    Month::February,
+    Month::March,
+    Month::April,
+    Month::May,
+    Month::June,
+    Month::July,
+    Month::August,
+    Month::September,
+    Month::October,
+    Month::November,
+    Month::December,
+];
+
+const RESET: &str = "\x1b[0m";
+const UNDERLINE: &str = "\x1b[4m";
+const NO_UNDERLINE: &str = "\x1b[24m";
+const REVERSE: &str = "\x1b[7m";
+const NO_REVERSE: &str = "\x1b[27m";
+const DIM: &str = "\x1b[2m";
+const NO_DIM: &str = "\x1b[22m";
+
+/// The usual RGB values of the 16 standard terminal colours, in escape code order.
+const ANSI_16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// Pick the symbols that mark highlighted days when colours are unavailable. Highlights without
+/// their own symbol get the first letter of their name that isn't already taken, or failing that a
+/// digit.
+pub(crate) fn symbols(highlights: &[Highlight]) -> Vec<String> {
+    let mut symbols = Vec::<String>::new();
+    for highlight in highlights {
+        let symbol = highlight.symbol().map(str::to_owned).unwrap_or_else(|| {
+            let candidates = highlight.name().chars().filter(|c| c.is_alphanumeric());
+            let symbol = (candidates.chain('1'..='9'))
+                .find(|c| !symbols.iter().any(|s| s.starts_with(*c)))
+                .unwrap_or('*');
+            symbol.to_string()
+        });
+        symbols.push(symbol);
+    }
+    symbols
+}
+
+/// Roughly how many columns `s` takes up in a terminal: two for emoji and East Asian wide
+/// characters, none for combining marks and variation selectors, and one for anything else. Emoji
+/// joined with zero-width joiners count as one.
+fn display_width(s: &str) -> usize {
+    const JOINER: char = '\u{200D}';
+    const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+    let mut width = 0;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = u32::from(c);
+        width += if c < 0x20
+            || (0x7F..0xA0).contains(&c)
+            || (0x300..=0x36F).contains(&c)
+            || (0x200B..=0x200F).contains(&c)
+            || (0xFE00..=0xFE0F).contains(&c)
+            || (0x1F3FB..=0x1F3FF).contains(&c)
+            || (0xE0000..=0xE007F).contains(&c)
+        {
+            0
+        } else if is_wide(c) || chars.peek() == Some(&EMOJI_PRESENTATION) {
+            2
+        } else {
+            1
+        };
+        while chars.next_if_eq(&JOINER).is_some() {
+            chars.next();
+        }
+    }
+    width
+}
+
+/// Whether a character is drawn two columns wide: East Asian wide and full-width characters, and
+/// emoji that are drawn as emoji by default.
+fn is_wide(c: u32) -> bool {
+    const WIDE: &[(u32, u32)] = &[
+        (0x1100, 0x115F),
+        (0x231A, 0x231B),
+        (0x23E9, 0x23EC),
+        (0x23F0, 0x23F0),
+        (0x23F3, 0x23F3),
+        (0x25FD, 0x25FE),
+        (0x2614, 0x2615),
+        (0x2648, 0x2653),
+        (0x267F, 0x267F),
+        (0x2693, 0x2693),
+        (0x26A1, 0x26A1),
+        (0x26AA, 0x26AB),
+        (0x26BD, 0x26BE),
+        (0x26C4, 0x26C5),
+        (0x26CE, 0x26CE),
+        (0x26D4, 0x26D4),
+        (0x26EA, 0x26EA),
+        (0x26F2, 0x26F3),
+        (0x26F5, 0x26F5),
+        (0x26FA, 0x26FA),
+        (0x26FD, 0x26FD),
+        (0x2705, 0x2705),
+        (0x270A, 0x270B),
+        (0x2728, 0x2728),
+        (0x274C, 0x274C),
+        (0x274E, 0x274E),
+        (0x2753, 0x2755),
+        (0x2757, 0x2757),
+        (0x2795, 0x2797),
+        (0x27B0, 0x27B0),
+        (0x27BF, 0x27BF),
+        (0x2B1B, 0x2B1C),
+        (0x2B50, 0x2B50),
+        (0x2B55, 0x2B55),
+        (0x2E80, 0x303E),
+        (0x3041, 0x4DBF),
+        (0x4E00, 0xA4CF),
+        (0xAC00, 0xD7A3),
+        (0xF900, 0xFAFF),
+        (0xFE30, 0xFE4F),
+        (0xFF00, 0xFF60),
+        (0xFFE0, 0xFFE6),
+        (0x1F004, 0x1F004),
+        (0x1F0CF, 0x1F0CF),
+        (0x1F18E, 0x1F18E),
+        (0x1F191, 0x1F19A),
+        (0x1F200, 0x1F2FF),
+        (0x1F300, 0x1F64F),
+        (0x1F680, 0x1F6FF),
+        (0x1F7E0, 0x1F7EB),
+        (0x1F900, 0x1F9FF),
+        (0x1FA70, 0x1FAFF),
+        (0x20000, 0x3FFFD),
+    ];
+    WIDE.iter()
+        .any(|&(first, last)| (first..=last).contains(&c))
+}
+
+fn is_light([r, g, b]: [u8; 3]) -> bool {
+    let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+    luma > 140.0
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.into_iter()
+        .zip(b)
+        .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn plain_month() {
+        let log = "[highlights]\n\
+            run = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2023-02-27.Mon = \"run\"\n\
+            2023-02-28.Tue = \"\"\n\
+            2023-03-01.Wed = \"run\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let mut options = Options {
+            year: None,
+            month: None,
+            colours: Colours::None,
+            as_of: None,
+            theme: Style::default(),
+        };
+        let mut out = Vec::new();
+        show(&log, &options, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "     March 2023\n\
+            Mo Tu We Th Fr Sa Su\n       \
+            1r 2  3  4  5\n \
+            6  7  8  9 10 11 12\n\
+            13 14 15 16 17 18 19\n\
+            20 21 22 23 24 25 26\n\
+            27 28 29 30 31\n\
+            \n\
+            r run\n",
+        );
+
+        options.theme.week_start = DayOfWeek(Weekday::Sunday);
+        let mut out = Vec::new();
+        show(&log, &options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Su Mo Tu We Th Fr Sa\n          1r"), "{out}");
+    }
+
+    #[test]
+    fn plain_as_of() {
+        let log = "[highlights]\n\
+            run = { shape = \"circle\", colour = \"#00FF00\" }\n\
+            [data]\n\
+            2023-03-01.Wed = \"run\"\n\
+            2023-03-02.Thu = \"\"\n\
+            2023-03-03.Fri = \"run\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let as_of = Date::from_calendar_date(2023, Month::March, 2).unwrap();
+        let mut out = Vec::new();
+        write_plain(&log, &Style::default(), Some(as_of), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        // Highlighted days keep their symbol even after the `as_of` day.
+        assert!(out.contains(" 1r 2< 3r 4· 5·\n"), "{out}");
+        assert!(out.contains(" 6· 7· 8· 9·10·11·12·\n"), "{out}");
+        assert!(
+            out.ends_with("\nr run  < as of 2023-03-02  · later\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn plain_wide_symbols() {
+        let log = "[highlights]\n\
+            run = { shape = \"circle\", colour = \"#00FF00\", symbol = \"🟢\" }\n\
+            rest = { shape = \"circle\", colour = \"#0000FF\", symbol = \"RR\" }\n\
+            [data]\n\
+            2023-03-01.Wed = \"run\"\n\
+            2023-03-02.Thu = \"rest\"\n"
+            .parse::<Log>()
+            .unwrap();
+        let mut out = Vec::new();
+        write_plain(&log, &Style::default(), None, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        // Every day makes room for the widest symbol, so the columns stay aligned.
+        // March is the last month on its line, so nothing follows its days.
+        assert!(out.contains("Su   Mo  Tu  We  Th  Fr  Sa  Su\n"), "{out}");
+        assert!(out.contains("    1🟢 2RR 3   4   5\n"), "{out}");
+        assert!(out.contains("  12    6   7   8   9  10  11  12\n"), "{out}");
+        assert_eq!(display_width("🟢"), 2);
+        assert_eq!(display_width("❤️"), 2);
+        assert_eq!(display_width("👩‍💻"), 2);
+        assert_eq!(display_width("é"), 1);
+    }
+
+    use super::display_width;
+    use super::show;
+    use super::write_plain;
+    use super::Colours;
+    use super::Options;
+    use crate::log::Log;
+    use crate::style::DayOfWeek;
+    use crate::style::Style;
+    use time::Date;
+    use time::Month;
+    use time::Weekday;
+}
+
+use crate::draw::PageYear;
+use crate::log::Colour;
+use crate::log::Highlight;
+use crate::log::Log;
+use crate::log::Shape;
+use crate::style::DayOfWeek;
+use crate::style::Style;
+use std::env;
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::ptr;
+use time::Date;
+use time::Month;
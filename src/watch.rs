@@ -0,0 +1,104 @@
+/// Re-render the PDF whenever the log, the theme file or any of the fonts change, until
+/// interrupted. `theme` is the argument to `--theme`, which is read again after every change.
+pub(crate) fn run(
+    mut options: Options,
+    theme: Option<&str>,
+    output: &Output,
+) -> anyhow::Result<()> {
+    // The log's `[style]` table and the theme can change which fonts are used, so they are updated
+    // after every successful parse.
+    let theme_file = theme.and_then(Style::theme_file);
+    let mut paths = iter::once(LOG_PATH)
+        .chain(theme_file)
+        .chain(options.theme.font_paths())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    let watched_files = 1 + usize::from(theme_file.is_some());
+
+    eprintln!("Watching {LOG_PATH} for changes…");
+
+    let mut last_modified = None;
+    loop {
+        let mut modified = modified_times(&paths);
+        if last_modified.as_ref() != Some(&modified) {
+            let theme = theme.map(Style::theme).transpose();
+            match theme {
+                Ok(theme) => {
+                    if let Some(theme) = theme {
+                        options.theme = theme;
+                    }
+                    if let Some(log) = rebuild(&options, output) {
+                        let fonts = options.style(&log).font_paths().map(str::to_owned);
+                        // Files saved while rendering must still look changed next time, so fonts
+                        // that were already watched keep the times from before the render, and
+                        // only new ones are read now.
+                        let times = (fonts.iter())
+                            .map(|font| match paths.iter().position(|path| path == font) {
+                                Some(i) => modified[i],
+                                None => modified_time(font),
+                            })
+                            .collect::<Vec<_>>();
+                        modified.truncate(watched_files);
+                        modified.extend(times);
+                        paths.truncate(watched_files);
+                        paths.extend(fonts);
+                    }
+                }
+                Err(e) => eprintln!("error: {:#}", anyhow::Error::new(e)),
+            }
+            last_modified = Some(modified);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn modified_times(paths: &[String]) -> Vec<Option<SystemTime>> {
+    paths.iter().map(|path| modified_time(path)).collect()
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Errors are printed rather than returned, since they will hopefully be fixed by the next save.
+/// Returns the log if it could be parsed.
+fn rebuild(options: &Options, output: &Output) -> Option<Log> {
+    let source = match fs::read_to_string(LOG_PATH) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("error: failed to read `{LOG_PATH}`: {e}");
+            return None;
+        }
+    };
+    let log = match source.parse::<Log>() {
+        Ok(log) => log,
+        Err(e) => {
+            eprint!("{}", e.snippet(&source, LOG_PATH));
+            return None;
+        }
+    };
+    match output.render(&log, options, OUTPUT_STEM) {
+        Ok(()) => eprintln!("Rendered {}", output.name()),
+        Err(e) => {
+            let e = anyhow::Error::new(e).context(format!("failed to render {}", output.name()));
+            eprintln!("error: {e:#}");
+        }
+    }
+    Some(log)
+}
+
+use crate::LOG_PATH;
+use crate::OUTPUT_STEM;
+use calendar::Log;
+use calendar::Options;
+use calendar::Output;
+use calendar::Style;
+use std::fs;
+use std::iter;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;